@@ -0,0 +1,298 @@
+//! Slash command schema shared by command registration and the interaction handlers.
+
+use super::locale::Locale;
+use std::collections::HashMap;
+use twilight_model::{
+    application::command::{Command, CommandOption, CommandOptionType, CommandOptionValue, CommandType},
+    guild::Permissions,
+    id::Id,
+};
+
+/// Names of the options (and `/edit` subcommands) that the handlers look up.
+pub mod option {
+    pub const QUIZ: &str = "quiz";
+    pub const QUESTION: &str = "question";
+    pub const CHOICE: &str = "choice";
+    pub const INDEX: &str = "index";
+    pub const ANSWER: &str = "answer";
+    pub const EXPIRATION: &str = "expiration";
+    pub const COLOR: &str = "color";
+    pub const ENABLED: &str = "enabled";
+    pub const CHANNEL: &str = "channel";
+    pub const LIMIT: &str = "limit";
+}
+
+/// Names of the `/settings` subcommands.
+pub mod setting {
+    pub const SHOW: &str = "show";
+    pub const EXPIRATION: &str = "expiration";
+    pub const COLOR: &str = "color";
+    pub const PING_WINNERS: &str = "ping-winners";
+    pub const ANSWER_CHANGES: &str = "answer-changes";
+    pub const ALLOW_CHANNEL: &str = "allow-channel";
+    pub const DISALLOW_CHANNEL: &str = "disallow-channel";
+    pub const RESET: &str = "reset";
+}
+
+/// Every top-level command that the bot handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Name {
+    Create,
+    List,
+    Start,
+    Add,
+    Remove,
+    Edit,
+    Help,
+    About,
+    Settings,
+}
+
+impl Name {
+    pub const ALL: [Self; 9] = [
+        Self::Create,
+        Self::List,
+        Self::Start,
+        Self::Add,
+        Self::Remove,
+        Self::Edit,
+        Self::Help,
+        Self::About,
+        Self::Settings,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::List => "list",
+            Self::Start => "start",
+            Self::Add => "add",
+            Self::Remove => "remove",
+            Self::Edit => "edit",
+            Self::Help => "help",
+            Self::About => "about",
+            Self::Settings => "settings",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|cmd| cmd.as_str() == name)
+    }
+}
+
+type Localizations = Option<HashMap<String, String>>;
+
+/// Translated names and descriptions of the command or option registered under `key` in every locale's catalog.
+fn localizations(key: &str) -> (Localizations, Localizations) {
+    let mut names = HashMap::new();
+    let mut descriptions = HashMap::new();
+    for locale in Locale::ALL {
+        let Some((name, description)) = (locale.catalog().command)(key) else {
+            continue;
+        };
+        for &code in locale.codes() {
+            names.insert(code.into(), name.into());
+            descriptions.insert(code.into(), description.into());
+        }
+    }
+    let non_empty = |map: HashMap<_, _>| Some(map).filter(|map| !map.is_empty());
+    (non_empty(names), non_empty(descriptions))
+}
+
+fn localized_option(kind: CommandOptionType, key: &str, name: &str, description: &str) -> CommandOption {
+    let (name_localizations, description_localizations) = localizations(key);
+    CommandOption {
+        autocomplete: None,
+        channel_types: None,
+        choices: None,
+        description: description.into(),
+        description_localizations,
+        kind,
+        max_length: None,
+        max_value: None,
+        min_length: None,
+        min_value: None,
+        name: name.into(),
+        name_localizations,
+        options: None,
+        required: Some(true),
+    }
+}
+
+fn option(kind: CommandOptionType, name: &str, description: &str) -> CommandOption {
+    localized_option(kind, name, name, description)
+}
+
+fn integer(name: &str, description: &str, min: i64, max: i64) -> CommandOption {
+    CommandOption {
+        min_value: Some(CommandOptionValue::Integer(min)),
+        max_value: Some(CommandOptionValue::Integer(max)),
+        ..option(CommandOptionType::Integer, name, description)
+    }
+}
+
+/// Subcommands are localized under `{parent}.{name}` since they may share their name with an option.
+fn subcommand(parent: Name, name: &str, description: &str, options: Vec<CommandOption>) -> CommandOption {
+    let key = format!("{}.{name}", parent.as_str());
+    let option = localized_option(CommandOptionType::SubCommand, &key, name, description);
+    CommandOption { options: Some(options), required: None, ..option }
+}
+
+fn command(name: Name, description: &str, options: Vec<CommandOption>) -> Command {
+    let (name_localizations, description_localizations) = localizations(name.as_str());
+    Command {
+        application_id: None,
+        default_member_permissions: None,
+        dm_permission: None,
+        description: description.into(),
+        description_localizations,
+        guild_id: None,
+        id: None,
+        kind: CommandType::ChatInput,
+        name: name.as_str().into(),
+        name_localizations,
+        nsfw: None,
+        options,
+        version: Id::new(1),
+    }
+}
+
+/// Builds the full list of slash commands to be registered with Discord.
+pub fn commands() -> Vec<Command> {
+    let qid = || integer(option::QUIZ, "The quiz ID.", 1, 32767);
+    let question = || option(CommandOptionType::String, option::QUESTION, "The question being asked.");
+    let choice = option(CommandOptionType::String, option::CHOICE, "The new choice to be added.");
+    let index = integer(option::INDEX, "The index of the choice to be removed.", 0, 24);
+    let answer = integer(option::ANSWER, "Index of the correct answer.", 0, 24);
+    let expiration =
+        || integer(option::EXPIRATION, "How long (in seconds) this quiz can be available once started.", 10, 600);
+    let enabled = || option(CommandOptionType::Boolean, option::ENABLED, "Whether to turn this on.");
+    let channel = || option(CommandOptionType::Channel, option::CHANNEL, "The channel in question.");
+    vec![
+        command(Name::Create, "Create a new quiz with default options.", vec![question()]),
+        command(Name::List, "List down all the quizzes you created.", Vec::new()),
+        command(
+            Name::Start,
+            "Start a previously created quiz. This deletes it from your list of quizzes.",
+            vec![qid()],
+        ),
+        command(Name::Add, "Add a new choice to the quiz.", vec![qid(), choice]),
+        command(Name::Remove, "Remove a choice from the quiz.", vec![qid(), index]),
+        command(
+            Name::Edit,
+            "Edit a property of the quiz.",
+            vec![
+                subcommand(Name::Edit, option::QUESTION, "Edit the question itself.", vec![qid(), question()]),
+                subcommand(Name::Edit, option::ANSWER, "Edit the correct answer of the quiz.", vec![qid(), answer]),
+                subcommand(
+                    Name::Edit,
+                    option::EXPIRATION,
+                    "Edit the expiration time of the quiz.",
+                    vec![qid(), expiration()],
+                ),
+            ],
+        ),
+        command(Name::Help, "Summon a help menu. Will be sent to you via a temporary message.", Vec::new()),
+        command(Name::About, "Some information about the bot, its development, and the creator.", Vec::new()),
+        Command {
+            default_member_permissions: Some(Permissions::MANAGE_GUILD),
+            dm_permission: Some(false),
+            ..command(
+                Name::Settings,
+                "Change how quizzes behave in this server.",
+                vec![
+                    subcommand(Name::Settings, setting::SHOW, "Show the current settings.", Vec::new()),
+                    subcommand(
+                        Name::Settings,
+                        setting::EXPIRATION,
+                        "Set the expiration time of new quizzes.",
+                        vec![expiration()],
+                    ),
+                    subcommand(
+                        Name::Settings,
+                        setting::COLOR,
+                        "Set the color of embeds.",
+                        vec![option(CommandOptionType::String, option::COLOR, "A hex color code such as #236EA5.")],
+                    ),
+                    subcommand(
+                        Name::Settings,
+                        setting::PING_WINNERS,
+                        "Choose whether the results ping the winners.",
+                        vec![enabled()],
+                    ),
+                    subcommand(
+                        Name::Settings,
+                        setting::ANSWER_CHANGES,
+                        "Limit how often participants may change their answer. Omit the limit to allow any number.",
+                        vec![CommandOption {
+                            required: Some(false),
+                            ..integer(
+                                option::LIMIT,
+                                "Number of allowed changes. Zero makes the first answer final.",
+                                0,
+                                10,
+                            )
+                        }],
+                    ),
+                    subcommand(
+                        Name::Settings,
+                        setting::ALLOW_CHANNEL,
+                        "Allow quizzes in a channel. Once any channel is allowed, quizzes only run in allowed ones.",
+                        vec![channel()],
+                    ),
+                    subcommand(
+                        Name::Settings,
+                        setting::DISALLOW_CHANNEL,
+                        "Stop allowing quizzes in a channel.",
+                        vec![channel()],
+                    ),
+                    subcommand(Name::Settings, setting::RESET, "Restore the default settings.", Vec::new()),
+                ],
+            )
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{commands, CommandOption, Locale, Name};
+
+    #[test]
+    fn every_command_has_a_handler() {
+        let registered = commands();
+        for command in &registered {
+            assert!(Name::parse(&command.name).is_some(), "`/{}` has no handler", command.name);
+        }
+        for name in Name::ALL {
+            let count = registered.iter().filter(|command| command.name == name.as_str()).count();
+            assert_eq!(count, 1, "`/{}` must be registered exactly once", name.as_str());
+        }
+    }
+
+    #[test]
+    fn every_command_and_option_is_localized() {
+        fn check(name: &str, option: &CommandOption) {
+            for code in Locale::Spanish.codes() {
+                let localized = option.name_localizations.as_ref().and_then(|names| names.get(*code));
+                assert!(localized.is_some(), "`{name}.{}` has no `{code}` name", option.name);
+                let localized = option.description_localizations.as_ref().and_then(|names| names.get(*code));
+                assert!(localized.is_some(), "`{name}.{}` has no `{code}` description", option.name);
+            }
+            for nested in option.options.iter().flatten() {
+                check(&option.name, nested);
+            }
+        }
+
+        for command in commands() {
+            for code in Locale::Spanish.codes() {
+                let localized = command.name_localizations.as_ref().and_then(|names| names.get(*code));
+                assert!(localized.is_some(), "`/{}` has no `{code}` name", command.name);
+                let localized = command.description_localizations.as_ref().and_then(|names| names.get(*code));
+                assert!(localized.is_some(), "`/{}` has no `{code}` description", command.name);
+            }
+            for option in &command.options {
+                check(&command.name, option);
+            }
+        }
+    }
+}
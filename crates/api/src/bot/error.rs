@@ -1,23 +1,88 @@
-use core::fmt::{self, Display};
+use super::{locale::Catalog, ChannelId};
+use core::time::Duration;
+use db::error::Constraint;
 
 #[derive(Debug)]
 pub enum Error {
-    BadInput,
+    /// The input violates a data constraint, which the user can correct.
+    BadInput(Constraint),
     NotFound,
     Schema,
     Database,
     Fatal,
+    /// The user (or the guild if `guild` is set) already stores `limit` quizzes.
+    QuotaExceeded {
+        limit: u16,
+        guild: bool,
+    },
+    /// The user or guild sent too many interactions. Holds how long to wait.
+    RateLimited(Duration),
+    /// The command only makes sense within a guild.
+    GuildOnly,
+    /// The member lacks the permissions to run the command.
+    Forbidden,
+    /// The guild only allows quizzes in the given channels.
+    ChannelNotAllowed(Vec<ChannelId>),
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(match self {
-            Self::BadInput => "Unacceptable input.",
-            Self::NotFound => "Resource not found.",
-            Self::Schema => "Discord provided an unexpected interaction schema.",
-            Self::Database => "We encountered an unexpected database error on our end.",
-            Self::Fatal => "Oops! We encountered a logic error on our end. This is a bug.",
-        })
+impl Error {
+    /// Explains the error to the user in the language of `catalog`.
+    pub fn message(&self, catalog: &Catalog) -> String {
+        match self {
+            &Self::RateLimited(wait) => {
+                // Round up so that retrying right on time is never rejected again
+                let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                (catalog.rate_limited)(secs)
+            }
+            &Self::QuotaExceeded { limit, guild: false } => (catalog.user_quota)(limit),
+            &Self::QuotaExceeded { limit, guild: true } => (catalog.guild_quota)(limit),
+            &Self::BadInput(constraint) => constraint_message(catalog, constraint),
+            Self::ChannelNotAllowed(channels) => {
+                let mentions: Vec<_> = channels.iter().map(|channel| format!("<#{channel}>")).collect();
+                (catalog.channel_not_allowed)(&mentions.join(", "))
+            }
+            Self::GuildOnly => catalog.guild_only.into(),
+            Self::Forbidden => catalog.forbidden.into(),
+            Self::NotFound => catalog.not_found.into(),
+            Self::Schema => catalog.schema.into(),
+            Self::Database => catalog.database.into(),
+            Self::Fatal => catalog.fatal.into(),
+        }
+    }
+
+    /// Name of the variant for use as a metric label.
+    pub const fn variant(&self) -> &'static str {
+        match self {
+            Self::BadInput(_) => "BadInput",
+            Self::NotFound => "NotFound",
+            Self::Schema => "Schema",
+            Self::Database => "Database",
+            Self::Fatal => "Fatal",
+            Self::QuotaExceeded { .. } => "QuotaExceeded",
+            Self::RateLimited(_) => "RateLimited",
+            Self::GuildOnly => "GuildOnly",
+            Self::Forbidden => "Forbidden",
+            Self::ChannelNotAllowed(_) => "ChannelNotAllowed",
+        }
+    }
+}
+
+/// Explains the violated constraint along with its limits.
+fn constraint_message(catalog: &Catalog, constraint: Constraint) -> String {
+    match constraint {
+        Constraint::QuestionLength => (catalog.question_length)(Constraint::MAX_TEXT_LEN),
+        Constraint::ChoiceLength => (catalog.choice_length)(Constraint::MAX_TEXT_LEN),
+        Constraint::ChoiceCount => (catalog.choice_count)(Constraint::MAX_CHOICES),
+        Constraint::AnswerRange { choices: 0 } => catalog.no_choices_to_answer.into(),
+        Constraint::AnswerRange { choices } => (catalog.answer_range)(choices),
+        Constraint::ChoiceIndex { choices: 0 } => catalog.no_choices_to_remove.into(),
+        Constraint::ChoiceIndex { choices } => (catalog.index_range)(choices),
+        Constraint::Expiration => (catalog.expiration)(*Constraint::EXPIRATION.start(), *Constraint::EXPIRATION.end()),
+        Constraint::Color => catalog.color.into(),
+        Constraint::ChannelCount => (catalog.channel_count)(Constraint::MAX_CHANNELS),
+        Constraint::AnswerChanges => (catalog.answer_changes)(Constraint::MAX_ANSWER_CHANGES),
+        // Webhooks and dead letters are never managed through the bot
+        Constraint::WebhookUrl | Constraint::WebhookSecret | Constraint::DeadLetterAttempts => catalog.bad_input.into(),
     }
 }
 
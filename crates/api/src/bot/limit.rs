@@ -0,0 +1,228 @@
+//! Token buckets that keep users and guilds from flooding the bot (and thus the database) with interactions.
+
+use super::command::Name;
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use dashmap::DashMap;
+use std::{collections::BTreeMap, time::Instant};
+
+/// Command name under which quiz answers (i.e., select menu interactions) are limited.
+pub const ANSWER: &str = "answer";
+/// Command name whose limit applies to every command without one of its own.
+const ANY: &str = "*";
+
+/// Allows a burst of `burst` interactions, then regains one every `period / burst`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    /// Seconds until one token is regained.
+    fn interval(self) -> f64 {
+        self.period.as_secs_f64() / f64::from(self.burst)
+    }
+}
+
+/// Per-command rate limits, written as comma-separated `COMMAND=BURST/SECONDS` pairs such as
+/// `*=10/10, create=3/60, answer=off`. The `*` entry applies to every command that is not listed, `answer` stands
+/// for quiz answers, and `off` lifts the limit.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimits(BTreeMap<&'static str, Option<RateLimit>>);
+
+impl RateLimits {
+    /// Looks up the limit for `command`, falling back to `*`.
+    fn get(&self, command: &str) -> Option<RateLimit> {
+        self.0.get(command).or_else(|| self.0.get(ANY)).copied().flatten()
+    }
+
+    /// Ten interactions per ten seconds for each user.
+    pub fn default_user() -> Self {
+        let limit = RateLimit { burst: 10, period: Duration::from_secs(10) };
+        Self(BTreeMap::from([(ANY, Some(limit))]))
+    }
+
+    /// A hundred commands per ten seconds for each guild. Answers are only limited per user so that large quizzes
+    /// are not cut short.
+    pub fn default_guild() -> Self {
+        let limit = RateLimit { burst: 100, period: Duration::from_secs(10) };
+        Self(BTreeMap::from([(ANY, Some(limit)), (ANSWER, None)]))
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimitsError(String);
+
+impl Display for RateLimitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RateLimitsError {}
+
+impl FromStr for RateLimits {
+    type Err = RateLimitsError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut limits = BTreeMap::new();
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let Some((command, limit)) = entry.split_once('=') else {
+                return Err(RateLimitsError(format!("expected `COMMAND=BURST/SECONDS` instead of `{entry}`")));
+            };
+            let command = match command.trim() {
+                ANY => ANY,
+                ANSWER => ANSWER,
+                other => Name::parse(other)
+                    .map(Name::as_str)
+                    .ok_or_else(|| RateLimitsError(format!("unknown command `{other}`")))?,
+            };
+            let limit = match limit.trim() {
+                "off" => None,
+                limit => {
+                    let error = || RateLimitsError(format!("expected `BURST/SECONDS` or `off` instead of `{limit}`"));
+                    let (burst, period) = limit.split_once('/').ok_or_else(error)?;
+                    let burst = burst.trim().parse().ok().filter(|&burst| burst > 0).ok_or_else(error)?;
+                    let period = period.trim().parse().ok().filter(|&secs| secs > 0).ok_or_else(error)?;
+                    Some(RateLimit { burst, period: Duration::from_secs(period) })
+                }
+            };
+            if limits.insert(command, limit).is_some() {
+                return Err(RateLimitsError(format!("duplicate limit for `{command}`")));
+            }
+        }
+        Ok(Self(limits))
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Refills the bucket up to `now`.
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed / limit.interval()).min(f64::from(limit.burst));
+        self.updated = now;
+    }
+}
+
+/// Buckets for one kind of key (i.e., users or guilds).
+struct Table {
+    limits: RateLimits,
+    buckets: DashMap<(u64, &'static str), Bucket>,
+    /// Checks since the last sweep of full buckets.
+    checks: AtomicU32,
+}
+
+impl Table {
+    /// Number of checks between sweeps of buckets that have been idle long enough to be full again.
+    const SWEEP_INTERVAL: u32 = 1024;
+
+    fn new(limits: RateLimits) -> Self {
+        Self { limits, buckets: DashMap::new(), checks: AtomicU32::new(0) }
+    }
+
+    /// Takes a token from the bucket of `key` for `command`. Returns how long to wait if there is none left.
+    fn take(&self, key: u64, command: &'static str, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = self.limits.get(command) else {
+            return Ok(());
+        };
+
+        if self.checks.fetch_add(1, Ordering::Relaxed) >= Self::SWEEP_INTERVAL {
+            self.checks.store(0, Ordering::Relaxed);
+            self.buckets.retain(|&(_, command), bucket| {
+                let Some(limit) = self.limits.get(command) else {
+                    return false;
+                };
+                bucket.refill(limit, now);
+                bucket.tokens < f64::from(limit.burst)
+            });
+        }
+
+        let burst = f64::from(limit.burst);
+        let mut bucket = self.buckets.entry((key, command)).or_insert(Bucket { tokens: burst, updated: now });
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) * limit.interval()))
+    }
+}
+
+pub struct Limiter {
+    users: Table,
+    guilds: Table,
+}
+
+impl Limiter {
+    pub fn new(users: RateLimits, guilds: RateLimits) -> Self {
+        Self { users: Table::new(users), guilds: Table::new(guilds) }
+    }
+
+    /// Counts an invocation of `command` by `user` in `guild` (if any). Returns how long to wait if either of them
+    /// has run out of tokens. A user that is limited does not use up the tokens of the guild.
+    pub fn check(&self, command: &'static str, user: u64, guild: Option<u64>) -> Result<(), Duration> {
+        self.check_at(command, user, guild, Instant::now())
+    }
+
+    fn check_at(&self, command: &'static str, user: u64, guild: Option<u64>, now: Instant) -> Result<(), Duration> {
+        self.users.take(user, command, now)?;
+        match guild {
+            Some(guild) => self.guilds.take(guild, command, now),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Duration, Instant, Limiter, RateLimit, RateLimits};
+
+    #[test]
+    fn limits_are_parsed_per_command() {
+        let limits: RateLimits = " *=10/10, create = 3/60 ,answer=off".parse().unwrap();
+        assert_eq!(limits.get("create"), Some(RateLimit { burst: 3, period: Duration::from_secs(60) }));
+        assert_eq!(limits.get("list"), Some(RateLimit { burst: 10, period: Duration::from_secs(10) }));
+        assert_eq!(limits.get("answer"), None);
+        assert_eq!("".parse::<RateLimits>().unwrap(), RateLimits::default());
+
+        for invalid in ["create", "quiz=1/1", "create=0/10", "create=1/0", "create=fast", "list=1/1,list=2/2"] {
+            assert!(invalid.parse::<RateLimits>().is_err(), "accepted `{invalid}`");
+        }
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let users = "create=2/10".parse().unwrap();
+        let guilds = "*=3/30".parse().unwrap();
+        let limiter = Limiter::new(users, guilds);
+        let start = Instant::now();
+
+        // The burst is used up, but other commands and users have buckets of their own
+        assert_eq!(limiter.check_at("create", 1, None, start), Ok(()));
+        assert_eq!(limiter.check_at("create", 1, None, start), Ok(()));
+        assert_eq!(limiter.check_at("create", 1, None, start), Err(Duration::from_secs(5)));
+        assert_eq!(limiter.check_at("list", 1, None, start), Ok(()));
+        assert_eq!(limiter.check_at("create", 2, None, start), Ok(()));
+
+        // One token is regained every five seconds
+        let later = start + Duration::from_secs(5);
+        assert_eq!(limiter.check_at("create", 1, None, later), Ok(()));
+        assert!(limiter.check_at("create", 1, None, later).is_err());
+
+        // Guilds are limited across all of their members
+        for user in 10..13 {
+            assert_eq!(limiter.check_at("list", user, Some(100), start), Ok(()));
+        }
+        assert_eq!(limiter.check_at("list", 13, Some(100), start), Err(Duration::from_secs(10)));
+        assert_eq!(limiter.check_at("list", 13, Some(200), start), Ok(()));
+    }
+}
@@ -0,0 +1,99 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    command: |_| None,
+
+    help_title: "Quizzo!",
+    help_description: "A list of commands for Quizzo.",
+    help_fields: &[
+        ("`/about`", "Some information about the bot, its development, and the creator."),
+        ("`/help`", "Summon the help page."),
+        ("`/list`", "Lists down your currently active (but not started) quizzes."),
+        ("`/create <question>`", "Creates a new quiz. Returns the generated quiz ID."),
+        ("`/add <qid> <choice>`", "Adds a new `<choice>` for quiz `<qid>`."),
+        ("`/remove <qid> <index>`", "Removes an existing choice by its `<index>` from quiz `<qid>`."),
+        ("`/edit question <qid> <question>`", "Sets a new question for quiz `<qid>`."),
+        ("`/edit expiration <qid> <expiration>`", "Sets a new expiration time for quiz `<qid>`."),
+        ("`/edit answer <qid> <answer>`", "Sets the correct answer for quiz `<qid>`. Expects a zero-indexed `<answer>`."),
+        ("`/start <qid>`", "Starts quiz `<qid>` in the current channel. The quiz is then removed from the list."),
+        ("`/settings`", "Changes how quizzes behave in this server. Requires the Manage Server permission."),
+    ],
+    about_title: "About Quizzo!",
+    about_description: "Quizzo is an [open-source](https://github.com/BastiDood/quizzo) Discord bot written in [Rust](https://www.rust-lang.org/) for making simple, timed, multiple-choice quizzes.",
+
+    quiz_added: |qid| format!("New quiz added: `{qid}`."),
+    no_quizzes: "You currently have no quizzes registered.",
+    quiz_summary: |qid, expiration| format!("Quiz `{qid}` is set to expire in {expiration} seconds."),
+    choice_added: |qid| format!("Successfully added new choice to quiz **[{qid}]**."),
+    choice_removed: |choice, qid| {
+        format!("Successfully removed choice ||{choice}|| from quiz **[{qid}]**. The answer has also been reset.")
+    },
+    question_edited: "The question property has been edited.",
+    answer_edited: "The answer property has been edited.",
+    expiration_edited: "The expiration property has been edited.",
+
+    settings_title: "Server Settings",
+    setting_expiration: "Expiration of new quizzes",
+    setting_color: "Embed color",
+    setting_ping_winners: "Ping winners",
+    setting_answer_changes: "Answer changes",
+    setting_channels: "Quiz channels",
+    seconds: |secs| format!("{secs} seconds"),
+    unlimited_changes: "Unlimited",
+    first_answer_final: "First answer is final",
+    change_limit: |limit| format!("Up to {limit}"),
+    yes: "Yes",
+    no: "No",
+    any_channel: "Any channel",
+
+    quiz_prompt: |expires_at, question| format!("**[Expires <t:{expires_at}:R>]:** {question}"),
+    answer_placeholder: "Your Answer",
+    view_answer: "View my answer",
+    answer_recorded: |label, expires_at| {
+        format!("Your answer **{label}** has been recorded. The quiz ends <t:{expires_at}:R>.")
+    },
+    answer_locked: |label| format!("Your first answer is final, so it remains **{label}**."),
+    answer_change_limit: |limit, label| {
+        let unit = if limit == 1 { "time" } else { "times" };
+        format!("You may only change your answer {limit} {unit}, so it remains **{label}**.")
+    },
+    current_answer: |label, expires_at| format!("Your answer is **{label}**. The quiz ends <t:{expires_at}:R>."),
+    no_answer: |expires_at| format!("You have not answered yet. The quiz ends <t:{expires_at}:R>."),
+    ended_early: "This quiz ended early because the bot is shutting down. ",
+    correct_answer: |correct| format!("The correct answer is: ||{correct}||. "),
+    no_winners: "Nobody got it right...",
+    congratulations: "Congratulations to",
+    other_winners: |rest| format!(" and {rest} others!"),
+
+    not_found: "Resource not found.",
+    schema: "Discord provided an unexpected interaction schema.",
+    database: "We encountered an unexpected database error on our end.",
+    fatal: "Oops! We encountered a logic error on our end. This is a bug.",
+    rate_limited: |secs| {
+        let unit = if secs == 1 { "second" } else { "seconds" };
+        format!("Slow down! Please wait {secs} {unit} before trying again.")
+    },
+    user_quota: |limit| format!("You already have {limit} quizzes, which is the limit. Start one of them first."),
+    guild_quota: |limit| format!("This server already has {limit} quizzes, which is the limit. Start one of them first."),
+    guild_only: "This command can only be used in a server.",
+    forbidden: "You need the Manage Server permission to do that.",
+    channel_not_allowed: |channels| format!("Quizzes in this server can only be started in {channels}."),
+    question_length: |max| format!("Questions must have between 1 and {max} characters."),
+    choice_length: |max| format!("Choices must have at most {max} characters."),
+    choice_count: |max| format!("This quiz already has {max} choices, which is the limit. Remove one with `/remove` first."),
+    no_choices_to_answer: "This quiz has no choices yet. Add some with `/add` before setting the answer.",
+    answer_range: |choices| {
+        let unit = if choices == 1 { "choice" } else { "choices" };
+        format!("The answer must be between 0 and {} since this quiz has {choices} {unit}.", choices - 1)
+    },
+    no_choices_to_remove: "This quiz has no choices to remove.",
+    index_range: |choices| {
+        let unit = if choices == 1 { "choice" } else { "choices" };
+        format!("The index must be between 0 and {} since this quiz has {choices} {unit}.", choices - 1)
+    },
+    expiration: |min, max| format!("The expiration must be between {min} and {max} seconds."),
+    color: "Colors must be hex codes between #000000 and #FFFFFF, such as #236EA5.",
+    channel_count: |max| format!("Quizzes may be allowed in at most {max} channels. Disallow one first."),
+    answer_changes: |max| format!("Answers may be changed at most {max} times."),
+    bad_input: "Unacceptable input.",
+};
@@ -0,0 +1,142 @@
+use super::Catalog;
+
+pub const CATALOG: Catalog = Catalog {
+    command: |key| {
+        Some(match key {
+            "create" => ("crear", "Crea un nuevo quiz con las opciones predeterminadas."),
+            "list" => ("listar", "Muestra todos los quizzes que has creado."),
+            "start" => ("iniciar", "Inicia un quiz creado previamente. Esto lo elimina de tu lista de quizzes."),
+            "add" => ("agregar", "Agrega una nueva opción al quiz."),
+            "remove" => ("quitar", "Quita una opción del quiz."),
+            "edit" => ("editar", "Edita una propiedad del quiz."),
+            "help" => ("ayuda", "Muestra un menú de ayuda. Se te enviará en un mensaje temporal."),
+            "about" => ("acerca-de", "Información sobre el bot, su desarrollo y su creador."),
+            "settings" => ("ajustes", "Cambia el comportamiento de los quizzes en este servidor."),
+            "edit.question" => ("pregunta", "Edita la pregunta en sí."),
+            "edit.answer" => ("respuesta", "Edita la respuesta correcta del quiz."),
+            "edit.expiration" => ("duración", "Edita el tiempo de expiración del quiz."),
+            "settings.show" => ("ver", "Muestra los ajustes actuales."),
+            "settings.expiration" => ("duración", "Establece el tiempo de expiración de los quizzes nuevos."),
+            "settings.color" => ("color", "Establece el color de los embeds."),
+            "settings.ping-winners" => ("mencionar-ganadores", "Elige si los resultados mencionan a los ganadores."),
+            "settings.answer-changes" => (
+                "cambiar-respuestas",
+                "Limita cuántas veces se puede cambiar la respuesta. Omite el límite para permitir cualquier cantidad.",
+            ),
+            "settings.allow-channel" => (
+                "permitir-canal",
+                "Permite quizzes en un canal. Si hay canales permitidos, los quizzes solo se inician en ellos.",
+            ),
+            "settings.disallow-channel" => ("prohibir-canal", "Deja de permitir quizzes en un canal."),
+            "settings.reset" => ("restablecer", "Restablece los ajustes predeterminados."),
+            "quiz" => ("quiz", "El ID del quiz."),
+            "question" => ("pregunta", "La pregunta que se hace."),
+            "choice" => ("opción", "La nueva opción que se agregará."),
+            "index" => ("índice", "El índice de la opción que se quitará."),
+            "answer" => ("respuesta", "Índice de la respuesta correcta."),
+            "expiration" => ("duración", "Cuánto tiempo (en segundos) estará disponible este quiz una vez iniciado."),
+            "color" => ("color", "Un código de color hexadecimal como #236EA5."),
+            "enabled" => ("activado", "Si se activa esta opción."),
+            "channel" => ("canal", "El canal en cuestión."),
+            "limit" => ("límite", "Cantidad de cambios permitidos. Cero hace que la primera respuesta sea definitiva."),
+            _ => return None,
+        })
+    },
+
+    help_title: "¡Quizzo!",
+    help_description: "Una lista de comandos de Quizzo.",
+    help_fields: &[
+        ("`/acerca-de`", "Información sobre el bot, su desarrollo y su creador."),
+        ("`/ayuda`", "Muestra la página de ayuda."),
+        ("`/listar`", "Muestra tus quizzes activos (pero aún no iniciados)."),
+        ("`/crear <pregunta>`", "Crea un nuevo quiz. Devuelve el ID generado del quiz."),
+        ("`/agregar <quiz> <opción>`", "Agrega una nueva `<opción>` al quiz `<quiz>`."),
+        ("`/quitar <quiz> <índice>`", "Quita una opción existente por su `<índice>` del quiz `<quiz>`."),
+        ("`/editar pregunta <quiz> <pregunta>`", "Establece una nueva pregunta para el quiz `<quiz>`."),
+        ("`/editar duración <quiz> <duración>`", "Establece un nuevo tiempo de expiración para el quiz `<quiz>`."),
+        (
+            "`/editar respuesta <quiz> <respuesta>`",
+            "Establece la respuesta correcta del quiz `<quiz>`. Espera una `<respuesta>` indexada desde cero.",
+        ),
+        ("`/iniciar <quiz>`", "Inicia el quiz `<quiz>` en el canal actual. Luego el quiz se quita de la lista."),
+        ("`/ajustes`", "Cambia el comportamiento de los quizzes en este servidor. Requiere el permiso Gestionar servidor."),
+    ],
+    about_title: "¡Acerca de Quizzo!",
+    about_description: "Quizzo es un bot de Discord de [código abierto](https://github.com/BastiDood/quizzo) escrito en [Rust](https://www.rust-lang.org/) para crear quizzes sencillos, cronometrados y de opción múltiple.",
+
+    quiz_added: |qid| format!("Nuevo quiz agregado: `{qid}`."),
+    no_quizzes: "Actualmente no tienes quizzes registrados.",
+    quiz_summary: |qid, expiration| format!("El quiz `{qid}` expirará en {expiration} segundos."),
+    choice_added: |qid| format!("Se agregó una nueva opción al quiz **[{qid}]**."),
+    choice_removed: |choice, qid| {
+        format!("Se quitó la opción ||{choice}|| del quiz **[{qid}]**. La respuesta también se ha restablecido.")
+    },
+    question_edited: "Se ha editado la pregunta.",
+    answer_edited: "Se ha editado la respuesta.",
+    expiration_edited: "Se ha editado el tiempo de expiración.",
+
+    settings_title: "Ajustes del servidor",
+    setting_expiration: "Duración de los quizzes nuevos",
+    setting_color: "Color de los embeds",
+    setting_ping_winners: "Mencionar ganadores",
+    setting_answer_changes: "Cambios de respuesta",
+    setting_channels: "Canales de quizzes",
+    seconds: |secs| format!("{secs} segundos"),
+    unlimited_changes: "Ilimitados",
+    first_answer_final: "La primera respuesta es definitiva",
+    change_limit: |limit| format!("Hasta {limit}"),
+    yes: "Sí",
+    no: "No",
+    any_channel: "Cualquier canal",
+
+    quiz_prompt: |expires_at, question| format!("**[Expira <t:{expires_at}:R>]:** {question}"),
+    answer_placeholder: "Tu respuesta",
+    view_answer: "Ver mi respuesta",
+    answer_recorded: |label, expires_at| {
+        format!("Se registró tu respuesta **{label}**. El quiz termina <t:{expires_at}:R>.")
+    },
+    answer_locked: |label| format!("Tu primera respuesta es definitiva, así que sigue siendo **{label}**."),
+    answer_change_limit: |limit, label| {
+        let unit = if limit == 1 { "vez" } else { "veces" };
+        format!("Solo puedes cambiar tu respuesta {limit} {unit}, así que sigue siendo **{label}**.")
+    },
+    current_answer: |label, expires_at| format!("Tu respuesta es **{label}**. El quiz termina <t:{expires_at}:R>."),
+    no_answer: |expires_at| format!("Aún no has respondido. El quiz termina <t:{expires_at}:R>."),
+    ended_early: "Este quiz terminó antes porque el bot se está apagando. ",
+    correct_answer: |correct| format!("La respuesta correcta es: ||{correct}||. "),
+    no_winners: "Nadie acertó...",
+    congratulations: "Felicidades a",
+    other_winners: |rest| format!(" y {rest} más!"),
+
+    not_found: "Recurso no encontrado.",
+    schema: "Discord envió una interacción con un esquema inesperado.",
+    database: "Encontramos un error inesperado de la base de datos de nuestro lado.",
+    fatal: "¡Ups! Encontramos un error de lógica de nuestro lado. Esto es un bug.",
+    rate_limited: |secs| {
+        let unit = if secs == 1 { "segundo" } else { "segundos" };
+        format!("¡Más despacio! Espera {secs} {unit} antes de volver a intentarlo.")
+    },
+    user_quota: |limit| format!("Ya tienes {limit} quizzes, que es el límite. Inicia uno de ellos primero."),
+    guild_quota: |limit| format!("Este servidor ya tiene {limit} quizzes, que es el límite. Inicia uno de ellos primero."),
+    guild_only: "Este comando solo se puede usar en un servidor.",
+    forbidden: "Necesitas el permiso Gestionar servidor para hacer eso.",
+    channel_not_allowed: |channels| format!("Los quizzes de este servidor solo se pueden iniciar en {channels}."),
+    question_length: |max| format!("Las preguntas deben tener entre 1 y {max} caracteres."),
+    choice_length: |max| format!("Las opciones deben tener como máximo {max} caracteres."),
+    choice_count: |max| format!("Este quiz ya tiene {max} opciones, que es el límite. Quita una con `/quitar` primero."),
+    no_choices_to_answer: "Este quiz aún no tiene opciones. Agrega algunas con `/agregar` antes de fijar la respuesta.",
+    answer_range: |choices| {
+        let unit = if choices == 1 { "opción" } else { "opciones" };
+        format!("La respuesta debe estar entre 0 y {} ya que este quiz tiene {choices} {unit}.", choices - 1)
+    },
+    no_choices_to_remove: "Este quiz no tiene opciones que quitar.",
+    index_range: |choices| {
+        let unit = if choices == 1 { "opción" } else { "opciones" };
+        format!("El índice debe estar entre 0 y {} ya que este quiz tiene {choices} {unit}.", choices - 1)
+    },
+    expiration: |min, max| format!("El tiempo de expiración debe estar entre {min} y {max} segundos."),
+    color: "Los colores deben ser códigos hexadecimales entre #000000 y #FFFFFF, como #236EA5.",
+    channel_count: |max| format!("Los quizzes se pueden permitir en {max} canales como máximo. Prohíbe uno primero."),
+    answer_changes: |max| format!("Las respuestas se pueden cambiar {max} veces como máximo."),
+    bad_input: "Entrada inaceptable.",
+};
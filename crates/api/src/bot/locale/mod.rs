@@ -0,0 +1,165 @@
+//! Message catalogs for everything the bot says, keyed by the locales that Discord sends with each interaction.
+
+mod en;
+mod es;
+
+use core::num::NonZeroI16;
+use twilight_model::application::interaction::Interaction;
+
+/// A language that the bot speaks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::English, Self::Spanish];
+
+    /// Matches a [Discord locale](https://discord.com/developers/docs/reference#locales) such as `en-US` or `es-419`.
+    pub fn parse(code: &str) -> Option<Self> {
+        match code.split('-').next()? {
+            "en" => Some(Self::English),
+            "es" => Some(Self::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Discord locales that registered commands are localized for.
+    pub const fn codes(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &["en-US", "en-GB"],
+            Self::Spanish => &["es-ES", "es-419"],
+        }
+    }
+
+    /// Language of replies that only the invoking user sees. Prefers their client language over the guild's.
+    pub fn of_user(interaction: &Interaction) -> Self {
+        let codes = [interaction.locale.as_deref(), interaction.guild_locale.as_deref()];
+        codes.into_iter().flatten().find_map(Self::parse).unwrap_or_default()
+    }
+
+    /// Language of messages that the whole channel sees. Prefers the guild language over the user's.
+    pub fn of_guild(interaction: &Interaction) -> Self {
+        let codes = [interaction.guild_locale.as_deref(), interaction.locale.as_deref()];
+        codes.into_iter().flatten().find_map(Self::parse).unwrap_or_default()
+    }
+
+    pub const fn catalog(self) -> &'static Catalog {
+        match self {
+            Self::English => &en::CATALOG,
+            Self::Spanish => &es::CATALOG,
+        }
+    }
+}
+
+/// Every user-facing string of the bot in one language. Messages with placeholders are functions so that each
+/// language may order (and pluralize) them as it sees fit.
+pub struct Catalog {
+    /// Localized `(name, description)` of a command or option for registration, looked up by the key that
+    /// [`super::command::commands`] assigns. English is the default and thus has none.
+    pub command: fn(&str) -> Option<(&'static str, &'static str)>,
+
+    pub help_title: &'static str,
+    pub help_description: &'static str,
+    /// Usage and description of each command in the help menu.
+    pub help_fields: &'static [(&'static str, &'static str)],
+    pub about_title: &'static str,
+    pub about_description: &'static str,
+
+    pub quiz_added: fn(NonZeroI16) -> String,
+    pub no_quizzes: &'static str,
+    /// Describes a quiz in `/list` given its ID and expiration.
+    pub quiz_summary: fn(NonZeroI16, i16) -> String,
+    pub choice_added: fn(NonZeroI16) -> String,
+    /// Confirms the removal of a choice given its text and the quiz ID.
+    pub choice_removed: fn(&str, NonZeroI16) -> String,
+    pub question_edited: &'static str,
+    pub answer_edited: &'static str,
+    pub expiration_edited: &'static str,
+
+    pub settings_title: &'static str,
+    pub setting_expiration: &'static str,
+    pub setting_color: &'static str,
+    pub setting_ping_winners: &'static str,
+    pub setting_answer_changes: &'static str,
+    pub setting_channels: &'static str,
+    /// Formats a duration in seconds.
+    pub seconds: fn(u16) -> String,
+    pub unlimited_changes: &'static str,
+    pub first_answer_final: &'static str,
+    /// Describes the given maximum number of answer changes.
+    pub change_limit: fn(u16) -> String,
+    pub yes: &'static str,
+    pub no: &'static str,
+    pub any_channel: &'static str,
+
+    /// Announces a started quiz given its expiry (as a UNIX timestamp) and question.
+    pub quiz_prompt: fn(u64, &str) -> String,
+    pub answer_placeholder: &'static str,
+    pub view_answer: &'static str,
+    /// Confirms the label of the picked choice along with the expiry (as a UNIX timestamp) of the quiz.
+    pub answer_recorded: fn(&str, u64) -> String,
+    /// Rejects an answer change under a quiz where the first answer is final, given the label of the kept answer.
+    pub answer_locked: fn(&str) -> String,
+    /// Rejects an answer change once the given number of changes were made, given the label of the kept answer.
+    pub answer_change_limit: fn(u16, &str) -> String,
+    /// Shows the label of the current answer along with the expiry (as a UNIX timestamp) of the quiz.
+    pub current_answer: fn(&str, u64) -> String,
+    /// Says that there is no answer yet, given the expiry (as a UNIX timestamp) of the quiz.
+    pub no_answer: fn(u64) -> String,
+    pub ended_early: &'static str,
+    pub correct_answer: fn(&str) -> String,
+    pub no_winners: &'static str,
+    pub congratulations: &'static str,
+    /// Summarizes the winners that do not fit in the announcement. Must end with `!`.
+    pub other_winners: fn(usize) -> String,
+
+    pub not_found: &'static str,
+    pub schema: &'static str,
+    pub database: &'static str,
+    pub fatal: &'static str,
+    /// Asks to wait for the given number of seconds.
+    pub rate_limited: fn(u64) -> String,
+    pub user_quota: fn(u16) -> String,
+    pub guild_quota: fn(u16) -> String,
+    pub guild_only: &'static str,
+    pub forbidden: &'static str,
+    /// Lists the mentions of the channels where quizzes are allowed.
+    pub channel_not_allowed: fn(&str) -> String,
+    /// Violations of the question length, choice length, and choice count given their maximum.
+    pub question_length: fn(usize) -> String,
+    pub choice_length: fn(usize) -> String,
+    pub choice_count: fn(usize) -> String,
+    pub no_choices_to_answer: &'static str,
+    /// Asks for an answer within a quiz of the given number of choices.
+    pub answer_range: fn(u16) -> String,
+    pub no_choices_to_remove: &'static str,
+    /// Asks for an index within a quiz of the given number of choices.
+    pub index_range: fn(u16) -> String,
+    /// Asks for an expiration between the given bounds.
+    pub expiration: fn(u16, u16) -> String,
+    pub color: &'static str,
+    /// Violation of the maximum number of allowed channels.
+    pub channel_count: fn(usize) -> String,
+    /// Violation of the maximum number of answer changes.
+    pub answer_changes: fn(u16) -> String,
+    pub bad_input: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+
+    #[test]
+    fn locales_fall_back_to_english() {
+        assert_eq!(Locale::parse("es-419"), Some(Locale::Spanish));
+        assert_eq!(Locale::parse("es-ES"), Some(Locale::Spanish));
+        assert_eq!(Locale::parse("en-GB"), Some(Locale::English));
+        assert_eq!(Locale::parse("fr"), None);
+        for locale in Locale::ALL {
+            assert!(locale.codes().iter().all(|code| Locale::parse(code) == Some(locale)));
+        }
+    }
+}
@@ -1,9 +1,28 @@
+pub mod command;
 mod error;
+pub mod limit;
+mod locale;
 
-use core::num::{NonZeroI16, NonZeroU64};
-use db::Database;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use command::{option, setting, Name};
+use limit::Limiter;
+use locale::{Catalog, Locale};
+
+use crate::{
+    metrics::Metrics,
+    webhook::{Answer, Finished, Webhooks},
+};
+use core::{
+    num::{NonZeroI16, NonZeroU64},
+    time::Duration,
+};
+use db::{error::Constraint, AnswerPolicy, GuildSettings, QuizStore};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::Instrument;
 use twilight_model::{
     application::interaction::{
         application_command::{CommandData, CommandDataOption, CommandOptionValue},
@@ -11,25 +30,72 @@ use twilight_model::{
         Interaction, InteractionData, InteractionType,
     },
     channel::message::{
-        component::{ActionRow, ComponentType, SelectMenu, SelectMenuOption},
+        component::{ActionRow, Button, ButtonStyle, ComponentType, SelectMenu, SelectMenuOption},
         embed::{EmbedAuthor, EmbedField},
         AllowedMentions, Component, Embed, MentionType, MessageFlags,
     },
+    guild::Permissions,
     http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
     id::{
-        marker::{ApplicationMarker, InteractionMarker, UserMarker},
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker, UserMarker},
         Id,
     },
     user::User,
 };
 
 type AppId = Id<ApplicationMarker>;
+type ChannelId = Id<ChannelMarker>;
+type GuildId = Id<GuildMarker>;
 type UserId = Id<UserMarker>;
 type InteractionId = Id<InteractionMarker>;
 
 struct Event {
     user: UserId,
+    /// The picked choice, or `None` if the participant only wants to see their current answer.
+    choice: Option<u32>,
+    /// Tells the participant what became of the event.
+    ack: oneshot::Sender<Ack>,
+}
+
+/// Reply of the session to an [`Event`].
+struct Ack {
+    outcome: Outcome,
+    /// Label of the current answer of the participant, if any.
+    current: Option<String>,
+    /// When the quiz expires (as a UNIX timestamp).
+    expires_at: u64,
+}
+
+enum Outcome {
+    Recorded,
+    /// The participant may no longer change their answer, as decided by the [`AnswerPolicy`] of the quiz.
+    Rejected(AnswerPolicy),
+    /// The current answer was merely looked up.
+    Viewed,
+    /// The picked choice is not in the quiz.
+    Invalid,
+}
+
+/// Suffix of the custom ID of the "View my answer" button, which follows the ID of the session.
+const VIEW_ANSWER_SUFFIX: &str = ":view";
+
+/// The latest accepted answer of a participant.
+struct Response {
     choice: u32,
+    elapsed: Duration,
+    /// Number of times the answer was changed so far.
+    changes: u16,
+}
+
+/// The interaction that starts a quiz, which is also where its results are announced.
+struct Origin {
+    id: InteractionId,
+    guild: Option<GuildId>,
+    channel: Option<ChannelId>,
+    /// Token for follow-up messages.
+    token: Box<str>,
+    /// Language of the channel that sees the quiz.
+    catalog: &'static Catalog,
 }
 
 type Channel = mpsc::UnboundedSender<Event>;
@@ -38,38 +104,300 @@ type Registry = dashmap::DashMap<InteractionId, Channel>;
 struct Inner {
     client: twilight_http::Client,
     quizzes: Registry,
+    metrics: Arc<Metrics>,
+    /// Reports finished quizzes to external systems.
+    webhooks: Webhooks,
+    /// Set to `true` once running quizzes should end early. Every quiz holds a receiver until it has announced
+    /// its results.
+    shutdown: watch::Sender<bool>,
+}
+
+impl Inner {
+    const FOLLOWUP_ATTEMPTS: u32 = 3;
+    const FOLLOWUP_BACKOFF: Duration = Duration::from_millis(500);
+
+    /// Announces the results of a quiz through its follow-up webhook, retrying transient failures with
+    /// exponential backoff. Falls back to a regular message in `channel` if the webhook cannot be used. The
+    /// mentioned winners are only pinged if `ping` is set.
+    async fn announce(&self, app_id: AppId, token: &str, channel: Option<ChannelId>, content: &str, ping: bool) {
+        let parse = if ping { vec![MentionType::Users] } else { Vec::new() };
+        let mentions = AllowedMentions { parse, ..Default::default() };
+        let mut backoff = Self::FOLLOWUP_BACKOFF;
+        for attempt in 1..=Self::FOLLOWUP_ATTEMPTS {
+            let interaction = self.client.interaction(app_id);
+            let followup = match interaction.create_followup(token).allowed_mentions(Some(&mentions)).content(content) {
+                Ok(followup) => followup,
+                Err(err) => {
+                    tracing::error!(error = %err, "invalid follow-up");
+                    break;
+                }
+            };
+            let err = match followup.await {
+                Ok(_) => return,
+                Err(err) => err,
+            };
+            if attempt == Self::FOLLOWUP_ATTEMPTS || !is_transient(&err) {
+                tracing::error!(error = %err, attempt, "follow-up failed");
+                break;
+            }
+            tracing::warn!(error = %err, attempt, ?backoff, "follow-up failed, retrying");
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+
+        if let Some(channel) = channel {
+            let result = match self.client.create_message(channel).allowed_mentions(Some(&mentions)).content(content) {
+                Ok(message) => message.await.map(drop).map_err(|err| err.to_string()),
+                Err(err) => Err(err.to_string()),
+            };
+            match result {
+                Ok(()) => {
+                    tracing::info!(channel = channel.get(), "results posted as a channel message instead");
+                    return;
+                }
+                Err(err) => tracing::error!(error = %err, channel = channel.get(), "cannot post results in channel"),
+            }
+        }
+
+        tracing::error!("results were not delivered");
+        self.metrics.record_followup_failure();
+    }
+}
+
+/// Whether a failed Discord request may succeed if sent again.
+fn is_transient(err: &twilight_http::Error) -> bool {
+    use twilight_http::error::ErrorType;
+    match err.kind() {
+        ErrorType::RequestError
+        | ErrorType::RequestTimedOut
+        | ErrorType::ChunkingResponse
+        | ErrorType::RatelimiterTicket
+        | ErrorType::ServiceUnavailable { .. } => true,
+        ErrorType::Response { status, .. } => status.is_server_error() || status.get() == 429,
+        _ => false,
+    }
+}
+
+/// Seconds since the UNIX epoch.
+fn unix_secs(time: SystemTime) -> error::Result<u64> {
+    time.duration_since(SystemTime::UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).map_err(|_| error::Error::Fatal)
+}
+
+/// Discord rejects message content longer than this many characters.
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Formats the results announcement. Winners that do not fit in a single message are summarized.
+fn results_content(catalog: &Catalog, early: bool, correct: &str, winners: &[UserId]) -> String {
+    let notice = if early { catalog.ended_early } else { "" };
+    let mut content = format!("{notice}{}", (catalog.correct_answer)(correct));
+    if winners.is_empty() {
+        content.push_str(catalog.no_winners);
+        return content;
+    }
+
+    // Leave room for the summary of the remaining winners
+    let reserved = (catalog.other_winners)(99999).len();
+    content.push_str(catalog.congratulations);
+    for (count, user) in winners.iter().enumerate() {
+        let mention = format!(" <@{user}>");
+        if content.len() + mention.len() + reserved > MAX_CONTENT_LENGTH {
+            let rest = winners.len() - count;
+            content.push_str(&(catalog.other_winners)(rest));
+            return content;
+        }
+        content.push_str(&mention);
+    }
+    content.push('!');
+    content
+}
+
+/// Parses a hex color code such as `#236EA5`, with or without the leading `#`.
+fn parse_color(code: &str) -> Option<u32> {
+    let hex = code.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Describes how often participants may change their answer.
+fn answer_policy_label(catalog: &Catalog, policy: AnswerPolicy) -> String {
+    match policy {
+        AnswerPolicy::LastWins => catalog.unlimited_changes.into(),
+        AnswerPolicy::FirstLocks => catalog.first_answer_final.into(),
+        AnswerPolicy::Limited(limit) => (catalog.change_limit)(limit.get()),
+    }
+}
+
+/// The value of the only option of a subcommand, which must be named `name`.
+fn sole_option<'a>(args: &'a [CommandDataOption], name: &str) -> error::Result<&'a CommandOptionValue> {
+    match args {
+        [CommandDataOption { name: arg, value }] if arg == name => Ok(value),
+        _ => Err(error::Error::Schema),
+    }
 }
 
-pub struct Bot {
+/// Presentation and quiz defaults.
+pub struct Options {
+    /// Embed color used when the user has no accent color.
+    pub brand_color: u32,
+    /// Expiration (in seconds) of newly created quizzes.
+    pub default_expiration: u16,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { brand_color: 0x236EA5, default_expiration: 10 }
+    }
+}
+
+pub struct Bot<S> {
     inner: Arc<Inner>,
-    db: Database,
+    options: Options,
+    /// Throttles interactions per user and per guild.
+    limiter: Limiter,
+    /// Shared with running quizzes, which may keep dead letters after the bot is gone.
+    db: Arc<S>,
     id: AppId,
 }
 
-impl Bot {
-    const BRAND_COLOR: u32 = 0x236EA5;
+impl<S: QuizStore> Bot<S> {
+    pub fn with_client(
+        db: S,
+        id: NonZeroU64,
+        client: twilight_http::Client,
+        metrics: Arc<Metrics>,
+        webhooks: Webhooks,
+        options: Options,
+        limiter: Limiter,
+    ) -> Self {
+        let (shutdown, _) = watch::channel(false);
+        let inner = Arc::new(Inner { client, quizzes: Registry::new(), metrics, webhooks, shutdown });
+        Self { inner, options, limiter, db: Arc::new(db), id: Id::from(id) }
+    }
 
-    pub fn new(db: Database, id: NonZeroU64, token: String) -> Self {
-        Self {
-            inner: Arc::new(Inner { client: twilight_http::Client::new(token), quizzes: Registry::new() }),
-            db,
-            id: Id::from(id),
+    /// Ends every running quiz early and waits until all of them have announced their results.
+    pub async fn shutdown(&self) {
+        self.inner.shutdown.send_replace(true);
+        self.inner.shutdown.closed().await;
+    }
+
+    /// Checks whether the quiz store can still serve queries.
+    pub async fn ping_database(&self) -> db::error::Result<()> {
+        self.db.ping().await
+    }
+
+    /// Number of quizzes currently accepting answers.
+    pub fn active_sessions(&self) -> usize {
+        self.inner.quizzes.len()
+    }
+
+    /// The underlying quiz store, for requests that do not come from Discord.
+    pub fn store(&self) -> &S {
+        &self.db
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Settings of the guild (if any) where an interaction happened.
+    async fn guild_settings(&self, guild: Option<GuildId>) -> error::Result<GuildSettings> {
+        match guild {
+            Some(guild) => self.db.get_guild_settings(guild.into_nonzero()).await.map_err(|_| error::Error::Database),
+            None => Ok(GuildSettings::default()),
+        }
+    }
+
+    /// Embed color of the guild, falling back to the brand color. Merely cosmetic, so database errors are
+    /// tolerated.
+    async fn embed_color(&self, guild: Option<GuildId>) -> Option<u32> {
+        match self.guild_settings(guild).await {
+            Ok(settings) => settings.color,
+            Err(err) => {
+                tracing::warn!(error = ?err, "cannot load embed color");
+                None
+            }
         }
     }
 
     pub async fn on_message(&self, interaction: Interaction) -> InteractionResponse {
-        let result = match interaction.kind {
-            InteractionType::Ping => return InteractionResponse { kind: InteractionResponseType::Pong, data: None },
-            InteractionType::ApplicationCommand => self.on_app_command(interaction).await,
-            InteractionType::MessageComponent => self.on_msg_component(interaction).await,
+        let kind = match interaction.kind {
+            InteractionType::Ping => "ping",
+            InteractionType::ApplicationCommand => "application_command",
+            InteractionType::MessageComponent => "message_component",
+            InteractionType::ApplicationCommandAutocomplete => "autocomplete",
+            InteractionType::ModalSubmit => "modal_submit",
+            _ => "unknown",
+        };
+        let command = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => Name::parse(&data.name).map_or("unknown", Name::as_str),
+            _ => "",
+        };
+        self.inner.metrics.record_interaction(kind, command);
+
+        // Answers are joined with the quiz session through the interaction ID that started it
+        let correlation_id = match &interaction.data {
+            Some(InteractionData::MessageComponent(data)) => {
+                let custom_id = data.custom_id.as_str();
+                custom_id.strip_suffix(VIEW_ANSWER_SUFFIX).unwrap_or(custom_id).parse().ok()
+            }
+            _ => Some(interaction.id.get()),
+        };
+        let span = tracing::info_span!(
+            "interaction",
+            id = interaction.id.get(),
+            correlation_id,
+            kind,
+            command,
+            guild = interaction.guild_id.map(Id::get),
+            channel = interaction.channel.as_ref().map(|channel| channel.id.get()),
+            user = interaction.author_id().map(Id::get),
+            latency_ms = tracing::field::Empty,
+        );
+
+        let start = Instant::now();
+        let limited = self.check_limits(&interaction, command);
+        let response = self.dispatch(interaction, limited).instrument(span.clone()).await;
+        let latency = start.elapsed().as_secs_f64() * 1000.0;
+        span.record("latency_ms", latency);
+        span.in_scope(|| tracing::info!(latency_ms = latency, "interaction handled"));
+        response
+    }
+
+    /// Takes a token from the buckets of the user and guild behind a command or an answer. Pings and unknown
+    /// commands are not limited.
+    fn check_limits(&self, interaction: &Interaction, command: &'static str) -> error::Result<()> {
+        let command = match interaction.kind {
+            InteractionType::ApplicationCommand if command != "unknown" => command,
+            InteractionType::MessageComponent => limit::ANSWER,
+            _ => return Ok(()),
+        };
+        let Some(user) = interaction.author_id() else {
+            return Ok(());
+        };
+        self.limiter.check(command, user.get(), interaction.guild_id.map(Id::get)).map_err(error::Error::RateLimited)
+    }
+
+    async fn dispatch(&self, interaction: Interaction, limited: error::Result<()>) -> InteractionResponse {
+        let catalog = Locale::of_user(&interaction).catalog();
+        let result = match (interaction.kind, limited) {
+            (InteractionType::Ping, _) => {
+                return InteractionResponse { kind: InteractionResponseType::Pong, data: None }
+            }
+            (_, Err(err)) => Err(err),
+            (InteractionType::ApplicationCommand, Ok(())) => self.on_app_command(interaction, catalog).await,
+            (InteractionType::MessageComponent, Ok(())) => self.on_msg_component(interaction, catalog).await,
             _ => Err(error::Error::Schema),
         };
         result.unwrap_or_else(|err| {
-            log::error!("interaction failed with `{err:?}`");
+            tracing::error!(error = ?err, "interaction failed");
+            self.inner.metrics.record_error(err.variant());
             InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
-                    content: Some(err.to_string()),
+                    content: Some(err.message(catalog)),
                     flags: Some(MessageFlags::EPHEMERAL),
                     tts: None,
                     allowed_mentions: None,
@@ -84,85 +412,59 @@ impl Bot {
         })
     }
 
-    async fn on_app_command(&self, interaction: Interaction) -> error::Result<InteractionResponse> {
+    async fn on_app_command(
+        &self,
+        interaction: Interaction,
+        catalog: &'static Catalog,
+    ) -> error::Result<InteractionResponse> {
+        // Quizzes are announced to the whole channel, which is better served by the language of the guild
+        let public = Locale::of_guild(&interaction).catalog();
+        let permissions = interaction.member.as_ref().and_then(|member| member.permissions);
         let user =
             interaction.member.and_then(|member| member.user).xor(interaction.user).ok_or(error::Error::Schema)?;
         let data = interaction.data.ok_or(error::Error::Schema)?;
         let InteractionData::ApplicationCommand(data) = data else {
             return Err(error::Error::Schema);
         };
-        log::info!("{data:?}");
+        tracing::debug!(options = ?data.options, "application command received");
 
         let iid = interaction.id;
+        let guild = interaction.guild_id;
+        let channel = interaction.channel.as_ref().map(|channel| channel.id);
         let token = interaction.token.into_boxed_str();
         let CommandData { name, options, .. } = *data;
 
-        match name.as_str() {
-            "create" => self.on_create_command(user.id, &options).await,
-            "list" => self.on_list_command(user).await,
-            "add" => self.on_add_choice(user.id, &options).await,
-            "remove" => self.on_remove_choice(user.id, &options).await,
-            "edit" => self.on_edit_command(user.id, &options).await,
-            "start" => self.on_start_command(user.id, &options, iid, token).await,
-            "help" => Ok(InteractionResponse {
+        let name = Name::parse(&name).ok_or(error::Error::Schema)?;
+        match name {
+            Name::Create => self.on_create_command(user.id, guild, &options, catalog).await,
+            Name::List => {
+                let color = self.embed_color(guild).await;
+                self.on_list_command(user, color, catalog).await
+            }
+            Name::Add => self.on_add_choice(user.id, &options, catalog).await,
+            Name::Remove => self.on_remove_choice(user.id, &options, catalog).await,
+            Name::Edit => self.on_edit_command(user.id, &options, catalog).await,
+            Name::Start => {
+                let origin = Origin { id: iid, guild, channel, token, catalog: public };
+                self.on_start_command(user.id, &options, origin).await
+            }
+            Name::Settings => self.on_settings_command(guild, permissions, &options, catalog).await,
+            Name::Help => Ok(InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
                     embeds: Some(vec![Embed {
-                        color: Some(Self::BRAND_COLOR),
-                        title: Some("Quizzo!".into()),
-                        description: Some("A list of commands for Quizzo.".into()),
-                        fields: vec![
-                            EmbedField {
+                        color: Some(self.embed_color(guild).await.unwrap_or(self.options.brand_color)),
+                        title: Some(catalog.help_title.into()),
+                        description: Some(catalog.help_description.into()),
+                        fields: catalog
+                            .help_fields
+                            .iter()
+                            .map(|&(usage, description)| EmbedField {
                                 inline: false,
-                                name: "`/about`".into(),
-                                value: "Some information about the bot, its development, and the creator.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/help`".into(),
-                                value: "Summon the help page.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/list`".into(),
-                                value: "Lists down your currently active (but not started) quizzes.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/create <question>`".into(),
-                                value: "Creates a new quiz. Returns the generated quiz ID.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/add <qid> <choice>`".into(),
-                                value: "Adds a new `<choice>` for quiz `<qid>`.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/remove <qid> <index>`".into(),
-                                value: "Removes an existing choice by its `<index>` from quiz `<qid>`.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/edit question <qid> <question>`".into(),
-                                value: "Sets a new question for quiz `<qid>`.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/edit expiration <qid> <expiration>`".into(),
-                                value: "Sets a new expiration time for quiz `<qid>`.".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/edit answer <qid> <answer>`".into(),
-                                value: "Sets the correct answer for quiz `<qid>`. Expects a zero-indexed".into(),
-                            },
-                            EmbedField {
-                                inline: false,
-                                name: "`/start <qid>`".into(),
-                                value: "Starts quiz `<qid>` in the current channel. The quiz is then removed from the list.".into(),
-                            },
-                        ],
+                                name: usage.into(),
+                                value: description.into(),
+                            })
+                            .collect(),
                         kind: "rich".into(),
                         author: None,
                         footer: None,
@@ -177,13 +479,13 @@ impl Bot {
                     ..Default::default()
                 }),
             }),
-            "about" => Ok(InteractionResponse {
+            Name::About => Ok(InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
                     embeds: Some(vec![Embed {
-                        color: Some(Self::BRAND_COLOR),
-                        title: Some("About Quizzo!".into()),
-                        description: Some("Quizzo is an [open-source](https://github.com/BastiDood/quizzo) Discord bot written in [Rust](https://www.rust-lang.org/) for making simple, timed, multiple-choice quizzes.".into()),
+                        color: Some(self.embed_color(guild).await.unwrap_or(self.options.brand_color)),
+                        title: Some(catalog.about_title.into()),
+                        description: Some(catalog.about_description.into()),
                         fields: Vec::new(),
                         kind: "rich".into(),
                         author: Some(EmbedAuthor {
@@ -204,41 +506,53 @@ impl Bot {
                     ..Default::default()
                 }),
             }),
-            _ => Err(error::Error::Schema),
         }
     }
 
     async fn on_create_command(
         &self,
         uid: Id<UserMarker>,
+        guild: Option<GuildId>,
         options: &[CommandDataOption],
+        catalog: &Catalog,
     ) -> error::Result<InteractionResponse> {
         let option = options.first().ok_or(error::Error::Schema)?;
         let CommandDataOption { name, value: CommandOptionValue::String(value) } = option else {
             return Err(error::Error::Schema);
         };
 
-        if name.as_str() != "question" {
+        if name.as_str() != option::QUESTION {
             return Err(error::Error::Schema);
         }
 
-        let qid = match self.db.init_quiz(uid.into_nonzero(), value.as_str()).await {
+        let expiration = self.guild_settings(guild).await?.expiration.unwrap_or(self.options.default_expiration);
+        let guild = guild.map(Id::into_nonzero);
+        let qid = match self.db.init_quiz(uid.into_nonzero(), guild, value.as_str(), expiration).await {
             Ok(id) => id,
-            Err(db::error::Error::BadInput) => return Err(error::Error::BadInput),
+            Err(db::error::Error::BadInput(constraint)) => return Err(error::Error::BadInput(constraint)),
+            Err(db::error::Error::QuotaExceeded { limit, guild }) => {
+                return Err(error::Error::QuotaExceeded { limit, guild })
+            }
             _ => return Err(error::Error::Database),
         };
 
         Ok(InteractionResponse {
             kind: InteractionResponseType::ChannelMessageWithSource,
             data: Some(InteractionResponseData {
-                content: Some(format!("New quiz added: `{qid}`.")),
+                content: Some((catalog.quiz_added)(qid)),
                 flags: Some(MessageFlags::EPHEMERAL),
                 ..Default::default()
             }),
         })
     }
 
-    async fn on_list_command(&self, user: User) -> error::Result<InteractionResponse> {
+    /// Lists the quizzes of `user`. Embeds take the `color` of the guild over the accent color of the user.
+    async fn on_list_command(
+        &self,
+        user: User,
+        color: Option<u32>,
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
         use db::TryStreamExt;
         let embeds: Vec<_> = self
             .db
@@ -265,9 +579,9 @@ impl Bot {
                 Embed {
                     fields,
                     kind: "rich".into(),
-                    color: Some(user.accent_color.unwrap_or(Self::BRAND_COLOR)),
+                    color: Some(color.or(user.accent_color).unwrap_or(self.options.brand_color)),
                     title: Some(question),
-                    description: Some(format!("Quiz `{id}` is set to expire in {expiration} seconds.")),
+                    description: Some((catalog.quiz_summary)(id, expiration)),
                     author: Some(EmbedAuthor {
                         name: format!("{}#{}", user.name, user.discriminator()),
                         icon_url: user
@@ -292,7 +606,7 @@ impl Bot {
             kind: InteractionResponseType::ChannelMessageWithSource,
             data: Some(if embeds.is_empty() {
                 InteractionResponseData {
-                    content: Some("You currently have no quizzes registered.".into()),
+                    content: Some(catalog.no_quizzes.into()),
                     flags: Some(MessageFlags::EPHEMERAL),
                     ..Default::default()
                 }
@@ -306,14 +620,19 @@ impl Bot {
         })
     }
 
-    async fn on_add_choice(&self, uid: UserId, options: &[CommandDataOption]) -> error::Result<InteractionResponse> {
+    async fn on_add_choice(
+        &self,
+        uid: UserId,
+        options: &[CommandDataOption],
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
         let [CommandDataOption { name: qid_arg, value: CommandOptionValue::Integer(qid) }, CommandDataOption { name: choice_arg, value: CommandOptionValue::String(choice) }] =
             options
         else {
             return Err(error::Error::Schema);
         };
 
-        if qid_arg.as_str() != "quiz" || choice_arg.as_str() != "choice" {
+        if qid_arg.as_str() != option::QUIZ || choice_arg.as_str() != option::CHOICE {
             return Err(error::Error::Schema);
         }
 
@@ -323,7 +642,7 @@ impl Bot {
             return Ok(InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
-                    content: Some(format!("Successfully added new choice to quiz **[{qid}]**.")),
+                    content: Some((catalog.choice_added)(qid)),
                     flags: Some(MessageFlags::EPHEMERAL),
                     ..Default::default()
                 }),
@@ -333,19 +652,24 @@ impl Bot {
         use db::error::Error as DbError;
         Err(match err {
             DbError::NotFound => error::Error::NotFound,
-            DbError::BadInput | DbError::TooMany => error::Error::BadInput,
-            DbError::Fatal => error::Error::Database,
+            DbError::BadInput(constraint) => error::Error::BadInput(constraint),
+            DbError::QuotaExceeded { .. } | DbError::Fatal => error::Error::Database,
         })
     }
 
-    async fn on_remove_choice(&self, uid: UserId, options: &[CommandDataOption]) -> error::Result<InteractionResponse> {
+    async fn on_remove_choice(
+        &self,
+        uid: UserId,
+        options: &[CommandDataOption],
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
         let [CommandDataOption { name: qid_arg, value: CommandOptionValue::Integer(qid) }, CommandDataOption { name: index_arg, value: CommandOptionValue::Integer(index) }] =
             options
         else {
             return Err(error::Error::Schema);
         };
 
-        if qid_arg.as_str() != "quiz" || index_arg.as_str() != "index" {
+        if qid_arg.as_str() != option::QUIZ || index_arg.as_str() != option::INDEX {
             return Err(error::Error::Schema);
         }
 
@@ -356,17 +680,23 @@ impl Bot {
             Ok(choice) => Ok(InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
-                    content: Some(format!("Successfully removed choice ||{choice}|| from quiz **[{qid}]**. The answer has also been reset.")),
+                    content: Some((catalog.choice_removed)(&choice, qid)),
                     flags: Some(MessageFlags::EPHEMERAL),
                     ..Default::default()
                 }),
             }),
             Err(db::error::Error::NotFound) => Err(error::Error::NotFound),
+            Err(db::error::Error::BadInput(constraint)) => Err(error::Error::BadInput(constraint)),
             _ => Err(error::Error::Database),
         }
     }
 
-    async fn on_edit_command(&self, uid: UserId, options: &[CommandDataOption]) -> error::Result<InteractionResponse> {
+    async fn on_edit_command(
+        &self,
+        uid: UserId,
+        options: &[CommandDataOption],
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
         let data = options.first().ok_or(error::Error::Schema)?;
         let CommandDataOption { name, value: CommandOptionValue::SubCommand(args) } = data else {
             return Err(error::Error::Schema);
@@ -378,7 +708,7 @@ impl Bot {
             return Err(error::Error::Schema);
         };
 
-        if qid_name.as_str() != "quiz" || name.as_str() != arg_name.as_str() {
+        if qid_name.as_str() != option::QUIZ || name.as_str() != arg_name.as_str() {
             return Err(error::Error::Schema);
         }
 
@@ -386,18 +716,18 @@ impl Bot {
         let qid = i16::try_from(*qid).map_err(|_| error::Error::Schema)?;
         let qid = NonZeroI16::new(qid).ok_or(error::Error::Schema)?;
 
-        let result = match (arg_name.as_str(), arg) {
-            ("question", CommandOptionValue::String(question)) => {
+        let (result, edited) = match (arg_name.as_str(), arg) {
+            (option::QUESTION, CommandOptionValue::String(question)) => {
                 let q = question.as_str();
-                self.db.set_question(uid, qid, q).await
+                (self.db.set_question(uid, qid, q).await, catalog.question_edited)
             }
-            ("answer", CommandOptionValue::Integer(index)) => {
+            (option::ANSWER, CommandOptionValue::Integer(index)) => {
                 let idx = u16::try_from(*index).map_err(|_| error::Error::Schema)?;
-                self.db.set_answer(uid, qid, idx).await
+                (self.db.set_answer(uid, qid, idx).await, catalog.answer_edited)
             }
-            ("expiration", CommandOptionValue::Integer(expiration)) => {
+            (option::EXPIRATION, CommandOptionValue::Integer(expiration)) => {
                 let exp = u16::try_from(*expiration).map_err(|_| error::Error::Schema)?;
-                self.db.set_expiration(uid, qid, exp).await
+                (self.db.set_expiration(uid, qid, exp).await, catalog.expiration_edited)
             }
             _ => return Err(error::Error::Schema),
         };
@@ -406,7 +736,7 @@ impl Bot {
             return Ok(InteractionResponse {
                 kind: InteractionResponseType::ChannelMessageWithSource,
                 data: Some(InteractionResponseData {
-                    content: Some(format!("The {arg_name} property has been edited.")),
+                    content: Some(edited.into()),
                     flags: Some(MessageFlags::EPHEMERAL),
                     ..Default::default()
                 }),
@@ -416,7 +746,7 @@ impl Bot {
         use db::error::Error as DbError;
         Err(match err {
             DbError::NotFound => error::Error::NotFound,
-            DbError::BadInput => error::Error::BadInput,
+            DbError::BadInput(constraint) => error::Error::BadInput(constraint),
             _ => error::Error::Database,
         })
     }
@@ -425,39 +755,52 @@ impl Bot {
         &self,
         uid: UserId,
         options: &[CommandDataOption],
-        iid: InteractionId,
-        token: Box<str>,
+        origin: Origin,
     ) -> error::Result<InteractionResponse> {
+        let Origin { id: iid, guild, channel, token, catalog } = origin;
         let option = options.first().ok_or(error::Error::Schema)?;
         let CommandDataOption { name, value: CommandOptionValue::Integer(qid) } = option else {
             return Err(error::Error::Schema);
         };
 
-        if name != "quiz" {
+        if name != option::QUIZ {
             return Err(error::Error::Schema);
         }
 
         let qid = i16::try_from(*qid).map_err(|_| error::Error::Schema)?;
         let qid = NonZeroI16::new(qid).ok_or(error::Error::Schema)?;
+
+        // Check the channel before the quiz is taken off the list
+        let GuildSettings { ping_winners, answer_policy, channels, .. } = self.guild_settings(guild).await?;
+        let allowed = channels.is_empty() || channel.is_some_and(|channel| channels.contains(&channel.into_nonzero()));
+        if !allowed {
+            return Err(error::Error::ChannelNotAllowed(channels.into_iter().map(Id::from).collect()));
+        }
+
         let db::RawQuiz { question, choices, answer, expiration } =
             match self.db.pop_quiz(uid.into_nonzero(), qid).await {
                 Ok(quiz) => quiz,
                 Err(db::error::Error::NotFound) => return Err(error::Error::NotFound),
                 _ => return Err(error::Error::Database),
             };
+        // `pop_quiz` only yields quizzes whose answer is set
         let Some(answer) = answer else {
-            return Err(error::Error::BadInput);
+            return Err(error::Error::Fatal);
         };
 
-        use std::time::SystemTime;
         let expiration = u64::try_from(expiration).map_err(|_| error::Error::Database)?;
-        let duration = core::time::Duration::from_secs(expiration);
-        let expires_at = SystemTime::now()
-            .checked_add(duration)
-            .ok_or(error::Error::Fatal)?
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map_err(|_| error::Error::Fatal)?
-            .as_secs();
+        let duration = Duration::from_secs(expiration);
+        let started_at = unix_secs(SystemTime::now())?;
+        let expires_at = started_at.checked_add(expiration).ok_or(error::Error::Fatal)?;
+
+        // Validate the answer before the quiz is registered as running
+        let answer = u32::try_from(answer).map_err(|_| error::Error::Database)?;
+        let count = u16::try_from(choices.len()).map_err(|_| error::Error::Database)?;
+        let correct = usize::try_from(answer)
+            .ok()
+            .and_then(|index| choices.get(index))
+            .ok_or(error::Error::BadInput(Constraint::AnswerRange { choices: count }))?
+            .clone();
 
         let (tx, mut rx) = mpsc::unbounded_channel();
         if self.inner.quizzes.insert(iid, tx).is_some() {
@@ -466,105 +809,348 @@ impl Bot {
 
         let app_id = self.id;
         let inner = self.inner.clone();
-        let correct = choices[usize::try_from(answer).unwrap()].clone();
-        tokio::spawn(async move {
-            let mut users = std::collections::BTreeSet::new();
+        let db = self.db.clone();
+        let mut shutdown = inner.shutdown.subscribe();
+        let content = (catalog.quiz_prompt)(expires_at, &question);
+        let menu = choices
+            .iter()
+            .enumerate()
+            .map(|(id, choice)| SelectMenuOption {
+                default: false,
+                description: None,
+                emoji: None,
+                label: choice.clone(),
+                value: id.to_string(),
+            })
+            .collect();
+        let span = tracing::info_span!(parent: None, "session", correlation_id = iid.get(), quiz = qid.get());
+        let session = async move {
+            tracing::info!(?duration, "quiz started");
+            let start = Instant::now();
+            let mut responses = BTreeMap::new();
             let mut sleep = core::pin::pin!(tokio::time::sleep(duration));
+            let mut early = false;
+            let label = |choice: u32| usize::try_from(choice).ok().and_then(|index| choices.get(index)).cloned();
             loop {
-                let Event { user, choice } = tokio::select! {
+                // Answers that were already acknowledged must be counted before ending early
+                let Event { user, choice, ack } = tokio::select! {
+                    biased;
                     Some(msg) = rx.recv() => msg,
                     _ = &mut sleep => break,
+                    Ok(_) = shutdown.wait_for(|&stop| stop) => {
+                        early = true;
+                        break;
+                    }
                     else => break,
                 };
-                if i64::from(answer) == i64::from(choice) {
-                    users.insert(user);
-                } else {
-                    users.remove(&user);
+                let elapsed = start.elapsed();
+                let outcome = match (choice, responses.entry(user)) {
+                    (None, _) => Outcome::Viewed,
+                    (Some(choice), _) if label(choice).is_none() => Outcome::Invalid,
+                    (Some(choice), Entry::Vacant(entry)) => {
+                        entry.insert(Response { choice, elapsed, changes: 0 });
+                        Outcome::Recorded
+                    }
+                    // Picking the same choice again is not a change
+                    (Some(choice), Entry::Occupied(entry)) if entry.get().choice == choice => Outcome::Recorded,
+                    (Some(choice), Entry::Occupied(mut entry)) if answer_policy.allows_change(entry.get().changes) => {
+                        let changes = entry.get().changes + 1;
+                        entry.insert(Response { choice, elapsed, changes });
+                        Outcome::Recorded
+                    }
+                    (Some(_), Entry::Occupied(_)) => Outcome::Rejected(answer_policy),
+                };
+
+                // The participant is gone if the acknowledgement can no longer be sent
+                let current = responses.get(&user).and_then(|response| label(response.choice));
+                if ack.send(Ack { outcome, current, expires_at }).is_err() {
+                    tracing::warn!(user = user.get(), "answer acknowledgement dropped");
                 }
             }
 
             drop(rx);
             inner.quizzes.remove(&iid);
 
-            let winners: Vec<_> = users.into_iter().map(|user| format!("<@{user}>")).collect();
-            let content = if winners.is_empty() {
-                format!("The correct answer is: ||{correct}||. Nobody got it right...")
-            } else {
-                let mentions = winners.join(" ").into_boxed_str();
-                format!("The correct answer is: ||{correct}||. Congratulations to {mentions}!")
+            let winners: Vec<_> =
+                responses.iter().filter(|(_, response)| response.choice == answer).map(|(&user, _)| user).collect();
+            let content = results_content(catalog, early, &correct, &winners);
+            tracing::info!(winners = winners.len(), early, "quiz ended");
+            inner.announce(app_id, &token, channel, &content, ping_winners).await;
+
+            // Quizzes outside of guilds (e.g., in DMs) have nowhere to report to
+            let Some(guild) = guild else {
+                return;
+            };
+            let finished = Finished {
+                event: Finished::EVENT,
+                guild_id: guild.to_string(),
+                channel_id: channel.map(|channel| channel.to_string()),
+                session_id: iid.to_string(),
+                quiz_id: qid.get(),
+                author_id: uid.to_string(),
+                question: &question,
+                choices: &choices,
+                answer,
+                started_at,
+                ended_at: unix_secs(SystemTime::now()).unwrap_or(started_at),
+                ended_early: early,
+                responses: responses
+                    .into_iter()
+                    .map(|(user, Response { choice, elapsed, .. })| Answer {
+                        user_id: user.to_string(),
+                        choice,
+                        correct: choice == answer,
+                        elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+                    })
+                    .collect(),
             };
-            inner
-                .client
-                .interaction(app_id)
-                .create_followup(&token)
-                .allowed_mentions(Some(&AllowedMentions { parse: vec![MentionType::Users], ..Default::default() }))
-                .content(&content)
-                .unwrap()
-                .await
-                .unwrap();
-        });
+            match serde_json::to_string(&finished) {
+                Ok(payload) => {
+                    inner.webhooks.deliver(&*db, &inner.metrics, guild.into_nonzero(), &payload, &shutdown).await;
+                }
+                Err(err) => tracing::error!("cannot encode webhook payload: {err}"),
+            }
+        };
+        tokio::spawn(session.instrument(span));
 
         Ok(InteractionResponse {
             kind: InteractionResponseType::ChannelMessageWithSource,
             data: Some(InteractionResponseData {
-                content: Some(format!("**[Expires <t:{expires_at}:R>]:** {question}")),
-                components: Some(vec![Component::ActionRow(ActionRow {
-                    components: vec![Component::SelectMenu(SelectMenu {
-                        custom_id: iid.to_string(),
-                        min_values: Some(1),
-                        max_values: Some(1),
-                        disabled: false,
-                        placeholder: Some("Your Answer".into()),
-                        options: choices
-                            .into_iter()
-                            .enumerate()
-                            .map(|(id, choice)| SelectMenuOption {
-                                default: false,
-                                description: None,
-                                emoji: None,
-                                label: choice,
-                                value: id.to_string(),
-                            })
-                            .collect(),
-                    })],
-                })]),
+                content: Some(content),
+                components: Some(vec![
+                    Component::ActionRow(ActionRow {
+                        components: vec![Component::SelectMenu(SelectMenu {
+                            custom_id: iid.to_string(),
+                            min_values: Some(1),
+                            max_values: Some(1),
+                            disabled: false,
+                            placeholder: Some(catalog.answer_placeholder.into()),
+                            options: menu,
+                        })],
+                    }),
+                    Component::ActionRow(ActionRow {
+                        components: vec![Component::Button(Button {
+                            custom_id: Some(format!("{iid}{VIEW_ANSWER_SUFFIX}")),
+                            disabled: false,
+                            emoji: None,
+                            label: Some(catalog.view_answer.into()),
+                            style: ButtonStyle::Secondary,
+                            url: None,
+                        })],
+                    }),
+                ]),
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Shows or changes the settings of a guild. Only members who may manage the guild can use this.
+    async fn on_settings_command(
+        &self,
+        guild: Option<GuildId>,
+        permissions: Option<Permissions>,
+        options: &[CommandDataOption],
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
+        let data = options.first().ok_or(error::Error::Schema)?;
+        let CommandDataOption { name, value: CommandOptionValue::SubCommand(args) } = data else {
+            return Err(error::Error::Schema);
+        };
+
+        // Discord hides the command from other members, but the default permissions may be overridden
+        let guild = guild.ok_or(error::Error::GuildOnly)?.into_nonzero();
+        if !permissions.is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD)) {
+            return Err(error::Error::Forbidden);
+        }
+
+        let mut settings = self.db.get_guild_settings(guild).await.map_err(|_| error::Error::Database)?;
+        match (name.as_str(), args.as_slice()) {
+            (setting::SHOW, []) => (),
+            (setting::RESET, []) => settings = GuildSettings::default(),
+            (setting::EXPIRATION, _) => {
+                let CommandOptionValue::Integer(expiration) = sole_option(args, option::EXPIRATION)? else {
+                    return Err(error::Error::Schema);
+                };
+                let expiration =
+                    u16::try_from(*expiration).map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+                settings.expiration = Some(expiration);
+            }
+            (setting::COLOR, _) => {
+                let CommandOptionValue::String(code) = sole_option(args, option::COLOR)? else {
+                    return Err(error::Error::Schema);
+                };
+                settings.color = Some(parse_color(code).ok_or(error::Error::BadInput(Constraint::Color))?);
+            }
+            (setting::PING_WINNERS, _) => {
+                let &CommandOptionValue::Boolean(enabled) = sole_option(args, option::ENABLED)? else {
+                    return Err(error::Error::Schema);
+                };
+                settings.ping_winners = enabled;
+            }
+            (setting::ANSWER_CHANGES, []) => settings.answer_policy = AnswerPolicy::LastWins,
+            (setting::ANSWER_CHANGES, _) => {
+                let &CommandOptionValue::Integer(limit) = sole_option(args, option::LIMIT)? else {
+                    return Err(error::Error::Schema);
+                };
+                let limit = u16::try_from(limit).map_err(|_| error::Error::BadInput(Constraint::AnswerChanges))?;
+                settings.answer_policy = AnswerPolicy::from_changes(Some(limit));
+            }
+            (setting::ALLOW_CHANNEL, _) => {
+                let &CommandOptionValue::Channel(channel) = sole_option(args, option::CHANNEL)? else {
+                    return Err(error::Error::Schema);
+                };
+                let channel = channel.into_nonzero();
+                if !settings.channels.contains(&channel) {
+                    settings.channels.push(channel);
+                }
+            }
+            (setting::DISALLOW_CHANNEL, _) => {
+                let &CommandOptionValue::Channel(channel) = sole_option(args, option::CHANNEL)? else {
+                    return Err(error::Error::Schema);
+                };
+                settings.channels.retain(|&allowed| allowed != channel.into_nonzero());
+            }
+            _ => return Err(error::Error::Schema),
+        }
+
+        if name.as_str() != setting::SHOW {
+            match self.db.set_guild_settings(guild, &settings).await {
+                Ok(()) => tracing::info!(setting = name.as_str(), "guild settings changed"),
+                Err(db::error::Error::BadInput(constraint)) => return Err(error::Error::BadInput(constraint)),
+                Err(_) => return Err(error::Error::Database),
+            }
+        }
+
+        let GuildSettings { expiration, color, ping_winners, answer_policy, channels } = settings;
+        let color = color.unwrap_or(self.options.brand_color);
+        let flag = |enabled| if enabled { catalog.yes } else { catalog.no };
+        let channels = if channels.is_empty() {
+            catalog.any_channel.into()
+        } else {
+            channels.iter().map(|channel| format!("<#{channel}>")).collect::<Vec<_>>().join(", ")
+        };
+        let values = [
+            (catalog.setting_expiration, (catalog.seconds)(expiration.unwrap_or(self.options.default_expiration))),
+            (catalog.setting_color, format!("#{color:06X}")),
+            (catalog.setting_ping_winners, flag(ping_winners).into()),
+            (catalog.setting_answer_changes, answer_policy_label(catalog, answer_policy)),
+            (catalog.setting_channels, channels),
+        ];
+        Ok(InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                embeds: Some(vec![Embed {
+                    color: Some(color),
+                    title: Some(catalog.settings_title.into()),
+                    fields: values
+                        .into_iter()
+                        .map(|(name, value)| EmbedField { inline: true, name: name.into(), value })
+                        .collect(),
+                    kind: "rich".into(),
+                    author: None,
+                    description: None,
+                    footer: None,
+                    image: None,
+                    provider: None,
+                    thumbnail: None,
+                    timestamp: None,
+                    url: None,
+                    video: None,
+                }]),
+                flags: Some(MessageFlags::EPHEMERAL),
                 ..Default::default()
             }),
         })
     }
 
-    async fn on_msg_component(&self, interaction: Interaction) -> error::Result<InteractionResponse> {
+    async fn on_msg_component(
+        &self,
+        interaction: Interaction,
+        catalog: &Catalog,
+    ) -> error::Result<InteractionResponse> {
         let User { id, .. } =
             interaction.member.and_then(|member| member.user).xor(interaction.user).ok_or(error::Error::Schema)?;
         let data = interaction.data.ok_or(error::Error::Schema)?;
-        log::info!("{data:?}");
 
-        let InteractionData::MessageComponent(MessageComponentInteractionData {
-            component_type: ComponentType::SelectMenu,
-            custom_id,
-            values,
-        }) = data
+        let InteractionData::MessageComponent(MessageComponentInteractionData { component_type, custom_id, values }) =
+            data
         else {
             return Err(error::Error::Schema);
         };
-        let choice =
-            values.into_iter().next().ok_or(error::Error::Schema)?.parse().map_err(|_| error::Error::Schema)?;
-        let iid = custom_id.parse().map_err(|_| error::Error::Schema)?;
+        let (iid, choice) = match (component_type, custom_id.strip_suffix(VIEW_ANSWER_SUFFIX)) {
+            (ComponentType::Button, Some(iid)) => (iid, None),
+            (ComponentType::SelectMenu, None) => {
+                let choice = values.first().ok_or(error::Error::Schema)?;
+                (custom_id.as_str(), Some(choice.parse().map_err(|_| error::Error::Schema)?))
+            }
+            _ => return Err(error::Error::Schema),
+        };
+        let iid = iid.parse().map_err(|_| error::Error::Schema)?;
+        tracing::debug!(?choice, "answer received");
 
+        // The session only hangs up once it stops taking answers
+        let (ack, outcome) = oneshot::channel();
         self.inner
             .quizzes
             .get(&iid)
             .ok_or(error::Error::NotFound)?
-            .send(Event { user: id, choice })
+            .send(Event { user: id, choice, ack })
             .map_err(|_| error::Error::NotFound)?;
+        let Ack { outcome, current, expires_at } = outcome.await.map_err(|_| error::Error::NotFound)?;
+        let content = match (outcome, current) {
+            (Outcome::Invalid, _) => return Err(error::Error::Schema),
+            (Outcome::Viewed, None) => (catalog.no_answer)(expires_at),
+            (Outcome::Viewed, Some(label)) => (catalog.current_answer)(&label, expires_at),
+            (Outcome::Recorded, Some(label)) => (catalog.answer_recorded)(&label, expires_at),
+            (Outcome::Rejected(AnswerPolicy::Limited(limit)), Some(label)) => {
+                (catalog.answer_change_limit)(limit.get(), &label)
+            }
+            (Outcome::Rejected(_), Some(label)) => (catalog.answer_locked)(&label),
+            // Accepted and rejected answers both leave an answer behind
+            (Outcome::Recorded | Outcome::Rejected(_), None) => return Err(error::Error::Fatal),
+        };
 
         Ok(InteractionResponse {
             kind: InteractionResponseType::ChannelMessageWithSource,
             data: Some(InteractionResponseData {
-                content: Some("Your answer has been successfully recorded.".into()),
+                content: Some(content),
                 flags: Some(MessageFlags::EPHEMERAL),
                 ..Default::default()
             }),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_color, results_content, Id, Locale, MAX_CONTENT_LENGTH};
+
+    #[test]
+    fn colors_are_hex_codes() {
+        assert_eq!(parse_color("#236EA5"), Some(0x236EA5));
+        assert_eq!(parse_color("ffffff"), Some(0xFFFFFF));
+        assert_eq!(parse_color(" #000000 "), Some(0));
+        assert_eq!(parse_color("#FFF"), None);
+        assert_eq!(parse_color("+12345"), None);
+        assert_eq!(parse_color("#1000000"), None);
+    }
+
+    #[test]
+    fn results_fit_in_one_message() {
+        let catalog = Locale::English.catalog();
+        let content = results_content(catalog, false, "Venus", &[]);
+        assert_eq!(content, "The correct answer is: ||Venus||. Nobody got it right...");
+
+        let winners = [Id::new(1), Id::new(2)];
+        let content = results_content(catalog, false, "Venus", &winners);
+        assert_eq!(content, "The correct answer is: ||Venus||. Congratulations to <@1> <@2>!");
+
+        let winners: Vec<_> = (1..=1000).map(|id| Id::new(100_000_000_000_000_000 + id)).collect();
+        for locale in Locale::ALL {
+            let content = results_content(locale.catalog(), true, &"x".repeat(100), &winners);
+            assert!(content.len() <= MAX_CONTENT_LENGTH);
+            let mentioned = content.matches("<@").count();
+            assert!(content.ends_with(&(locale.catalog().other_winners)(winners.len() - mentioned)));
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{
+    bot::{
+        command,
+        limit::{Limiter, RateLimits},
+        Bot, Options,
+    },
+    metrics::{Instrumented, Metrics},
+    oauth::{OAuth, OAuthConfig},
+    replay::ReplayGuard,
+    webhook::Webhooks,
+    App,
+};
+use core::{
+    fmt::{self, Display},
+    num::NonZeroU64,
+    str::FromStr,
+    time::Duration,
+};
+use db::QuizStore;
+use ed25519_dalek::VerifyingKey;
+use hyper::Uri;
+use std::sync::Arc;
+use twilight_model::id::Id;
+
+/// Base URL of the Discord REST API, such as `https://discord.com` or `http://localhost:8080`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiUrl {
+    /// Host and optional port.
+    host: Box<str>,
+    /// Whether to use plaintext HTTP instead of HTTPS.
+    http: bool,
+}
+
+#[derive(Debug)]
+pub enum ApiUrlError {
+    /// The URL is malformed.
+    Syntax,
+    /// Only `http` and `https` are supported.
+    Scheme,
+    /// The URL has no host.
+    Host,
+    /// The URL has a path, query, or fragment. The `/api/v10` prefix is always appended.
+    Path,
+}
+
+impl Display for ApiUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Syntax => "malformed API URL",
+            Self::Scheme => "API URL scheme must be `http` or `https`",
+            Self::Host => "API URL has no host",
+            Self::Path => "API URL must not have a path or query",
+        })
+    }
+}
+
+impl std::error::Error for ApiUrlError {}
+
+impl FromStr for ApiUrl {
+    type Err = ApiUrlError;
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let uri: Uri = url.parse().map_err(|_| ApiUrlError::Syntax)?;
+        let http = match uri.scheme_str() {
+            Some("http") => true,
+            Some("https") => false,
+            _ => return Err(ApiUrlError::Scheme),
+        };
+        let host = uri.authority().ok_or(ApiUrlError::Host)?.as_str().into();
+        if !matches!(uri.path(), "" | "/") || uri.query().is_some() {
+            return Err(ApiUrlError::Path);
+        }
+        Ok(Self { host, http })
+    }
+}
+
+/// Strategy for respecting Discord's rate limits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Ratelimiter {
+    /// Track rate limit buckets in memory.
+    #[default]
+    InMemory,
+    /// Send requests immediately. Useful when a proxy already handles rate limits.
+    Disabled,
+}
+
+#[derive(Debug)]
+pub struct RatelimiterError;
+
+impl Display for RatelimiterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ratelimiter must be `memory` or `none`")
+    }
+}
+
+impl std::error::Error for RatelimiterError {}
+
+impl FromStr for Ratelimiter {
+    type Err = RatelimiterError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "memory" => Ok(Self::InMemory),
+            "none" => Ok(Self::Disabled),
+            _ => Err(RatelimiterError),
+        }
+    }
+}
+
+pub struct AppBuilder {
+    id: NonZeroU64,
+    token: String,
+    api_url: Option<ApiUrl>,
+    proxy: Option<String>,
+    timeout: Option<Duration>,
+    ratelimiter: Ratelimiter,
+    replay_window: Duration,
+    replay_capacity: usize,
+    max_body_size: usize,
+    read_timeout: Duration,
+    oauth: Option<OAuthConfig>,
+    webhook_attempts: u16,
+    webhook_backoff: Duration,
+    user_rate_limits: RateLimits,
+    guild_rate_limits: RateLimits,
+    options: Options,
+}
+
+impl AppBuilder {
+    const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+    /// How long to wait for a request body (or a TLS handshake) unless configured otherwise.
+    pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(id: NonZeroU64, token: String) -> Self {
+        Self {
+            id,
+            token,
+            api_url: None,
+            proxy: None,
+            timeout: None,
+            ratelimiter: Ratelimiter::default(),
+            replay_window: ReplayGuard::DEFAULT_WINDOW,
+            replay_capacity: ReplayGuard::DEFAULT_CAPACITY,
+            max_body_size: Self::DEFAULT_MAX_BODY_SIZE,
+            read_timeout: Self::DEFAULT_READ_TIMEOUT,
+            oauth: None,
+            webhook_attempts: Webhooks::DEFAULT_ATTEMPTS,
+            webhook_backoff: Webhooks::DEFAULT_BACKOFF,
+            user_rate_limits: RateLimits::default_user(),
+            guild_rate_limits: RateLimits::default_guild(),
+            options: Options::default(),
+        }
+    }
+
+    /// Sends Discord REST requests to `url` instead of `https://discord.com`.
+    pub fn api_url(mut self, url: ApiUrl) -> Self {
+        self.api_url = Some(url);
+        self
+    }
+
+    /// Routes Discord REST requests through a [Twilight HTTP proxy](https://github.com/twilight-rs/http-proxy)
+    /// listening on `host` over plaintext HTTP. Takes precedence over [`api_url`](Self::api_url).
+    pub fn proxy(mut self, host: String) -> Self {
+        self.proxy = Some(host);
+        self
+    }
+
+    /// Sets the timeout for each Discord REST request. Defaults to 10 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn ratelimiter(mut self, ratelimiter: Ratelimiter) -> Self {
+        self.ratelimiter = ratelimiter;
+        self
+    }
+
+    /// Rejects interactions whose signed timestamp is further than `window` from the current time.
+    /// Defaults to 5 minutes.
+    pub fn replay_window(mut self, window: Duration) -> Self {
+        self.replay_window = window;
+        self
+    }
+
+    /// Sets how many recent interaction IDs to remember for rejecting duplicates. Zero disables the check.
+    /// Defaults to 10000.
+    pub fn replay_capacity(mut self, capacity: usize) -> Self {
+        self.replay_capacity = capacity;
+        self
+    }
+
+    /// Rejects request bodies larger than `bytes` with `413 Payload Too Large`. Defaults to 256 KiB.
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Rejects requests whose body is not fully received within `timeout` with `408 Request Timeout`.
+    /// Defaults to 10 seconds.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Enables browser login at `/oauth/login` with the application's OAuth2 credentials. Sessions then
+    /// authenticate REST API requests just like API keys.
+    pub fn oauth(mut self, config: OAuthConfig) -> Self {
+        self.oauth = Some(config);
+        self
+    }
+
+    /// Gives up on delivering a webhook payload after `attempts` and keeps it as a dead letter. Defaults to 5.
+    pub fn webhook_attempts(mut self, attempts: u16) -> Self {
+        self.webhook_attempts = attempts;
+        self
+    }
+
+    /// Waits `backoff` before retrying a webhook, which doubles after every attempt. Defaults to 1 second.
+    pub fn webhook_backoff(mut self, backoff: Duration) -> Self {
+        self.webhook_backoff = backoff;
+        self
+    }
+
+    /// Limits how often each user may run every command or answer quizzes. Defaults to `*=10/10`.
+    pub fn user_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.user_rate_limits = limits;
+        self
+    }
+
+    /// Limits how often the members of each guild may run every command or answer quizzes altogether. Defaults to
+    /// `*=100/10, answer=off`.
+    pub fn guild_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.guild_rate_limits = limits;
+        self
+    }
+
+    /// Sets the embed color used when a user has no accent color. Defaults to `0x236EA5`.
+    pub fn brand_color(mut self, color: u32) -> Self {
+        self.options.brand_color = color;
+        self
+    }
+
+    /// Sets how long (in seconds) newly created quizzes run once started. Defaults to 10 seconds.
+    pub fn default_expiration(mut self, expiration: u16) -> Self {
+        self.options.default_expiration = expiration;
+        self
+    }
+
+    fn client(self) -> (NonZeroU64, twilight_http::Client) {
+        let Self { id, token, api_url, proxy, timeout, ratelimiter, .. } = self;
+
+        let mut client = twilight_http::Client::builder().token(token);
+        if let Some(host) = proxy {
+            client = client.proxy(host, true);
+        } else if let Some(ApiUrl { host, http }) = api_url {
+            client = client.proxy(host.into(), http);
+        }
+        if let Some(timeout) = timeout {
+            client = client.timeout(timeout);
+        }
+        if ratelimiter == Ratelimiter::Disabled {
+            client = client.ratelimiter(None);
+        }
+
+        (id, client.build())
+    }
+
+    pub fn build<S: QuizStore>(mut self, db: S, public: VerifyingKey) -> App<S> {
+        let replay = ReplayGuard::new(self.replay_window, self.replay_capacity);
+        let max_body_size = self.max_body_size;
+        let read_timeout = self.read_timeout;
+        let options = core::mem::take(&mut self.options);
+        let limits = core::mem::take(&mut self.user_rate_limits);
+        let limiter = Limiter::new(limits, core::mem::take(&mut self.guild_rate_limits));
+        let origin = match &self.api_url {
+            Some(ApiUrl { host, http: true }) => format!("http://{host}"),
+            Some(ApiUrl { host, http: false }) => format!("https://{host}"),
+            None => "https://discord.com".into(),
+        };
+        let http = crate::https_client();
+        let webhooks = Webhooks::new(http.clone(), self.webhook_attempts, self.webhook_backoff);
+        let oauth = self.oauth.take().map(|config| OAuth::new(self.id, config, origin, http));
+        let (id, client) = self.client();
+        let metrics = Arc::new(Metrics::default());
+        let db = Instrumented::new(db, metrics.clone());
+        App {
+            bot: Bot::with_client(db, id, client, metrics.clone(), webhooks, options, limiter),
+            metrics,
+            public,
+            replay,
+            max_body_size,
+            read_timeout,
+            oauth,
+        }
+    }
+
+    /// Overwrites the application's slash commands with the ones that the bot handles.
+    /// Registers them only in `guild` if given, which takes effect immediately unlike global commands.
+    pub async fn register(self, guild: Option<NonZeroU64>) -> Result<(), twilight_http::Error> {
+        let (id, client) = self.client();
+        let commands = command::commands();
+        let interaction = client.interaction(Id::from(id));
+        match guild {
+            Some(guild) => interaction.set_guild_commands(Id::from(guild), &commands).await?,
+            None => interaction.set_global_commands(&commands).await?,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiUrl, ApiUrlError};
+
+    #[test]
+    fn parse_api_url() {
+        let url: ApiUrl = "https://discord.com".parse().unwrap();
+        assert_eq!(url, ApiUrl { host: "discord.com".into(), http: false });
+
+        let url: ApiUrl = "http://127.0.0.1:8080/".parse().unwrap();
+        assert_eq!(url, ApiUrl { host: "127.0.0.1:8080".into(), http: true });
+
+        assert!(matches!("ftp://discord.com".parse::<ApiUrl>(), Err(ApiUrlError::Scheme)));
+        assert!(matches!("discord.com".parse::<ApiUrl>(), Err(ApiUrlError::Scheme)));
+        assert!(matches!("https://discord.com/api/v10".parse::<ApiUrl>(), Err(ApiUrlError::Path)));
+        assert!(matches!("https://discord.com/?v=10".parse::<ApiUrl>(), Err(ApiUrlError::Path)));
+    }
+}
@@ -1,44 +1,129 @@
 mod bot;
+mod builder;
+mod metrics;
+mod oauth;
+mod replay;
+mod rest;
+mod webhook;
 
 use bot::Bot;
-use core::num::NonZeroU64;
+use core::{fmt::Display, num::NonZeroU64, time::Duration};
 use http_body_util::Full;
 use hyper::{
-    body::{Bytes, Incoming},
+    body::{Body, Bytes},
     HeaderMap, Method, Response, StatusCode,
 };
+use serde_json::json;
 
-pub use db::{Client, Config, Database, NoTls};
+use metrics::{Instrumented, Metrics};
+use oauth::OAuth;
+use replay::ReplayGuard;
+use std::{sync::Arc, time::Instant};
+use twilight_model::application::interaction::Interaction;
+
+pub use bot::limit::{RateLimits, RateLimitsError};
+pub use builder::{ApiUrl, ApiUrlError, AppBuilder, Ratelimiter, RatelimiterError};
+#[cfg(feature = "rustls")]
+pub use db::tls;
+pub use db::{error::Constraint, Client, Config, Database, DeadLetter, NoTls, QuizStore, Quota};
 pub use ed25519_dalek::VerifyingKey;
+pub use oauth::OAuthConfig;
+pub use rest::ApiKey;
+pub use webhook::{sign_webhook, webhook_secret};
+
+/// HTTP client for requests to Discord and webhooks outside of the bot's Twilight client.
+type HttpsClient = hyper_util::client::legacy::Client<
+    hyper_rustls::HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>,
+    Full<Bytes>,
+>;
+
+fn https_client() -> HttpsClient {
+    use hyper_rustls::HttpsConnectorBuilder;
+    let builder = HttpsConnectorBuilder::new().with_native_roots().unwrap_or_else(|err| {
+        // Plaintext endpoints (e.g., local stand-ins) still work, but HTTPS requests will fail
+        tracing::warn!("cannot load native root certificates: {err}");
+        let roots = rustls::RootCertStore::empty();
+        let config = rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        HttpsConnectorBuilder::new().with_tls_config(config)
+    });
+    let connector = builder.https_or_http().enable_http1().build();
+    hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new()).build(connector)
+}
 
-pub struct App {
+pub struct App<S> {
     /// Command handler.
-    bot: Bot,
+    bot: Bot<Instrumented<S>>,
+    /// Counters exposed at `GET /metrics`.
+    metrics: Arc<Metrics>,
     /// Ed25519 public key.
     public: VerifyingKey,
+    /// Rejects stale and duplicate interactions.
+    replay: ReplayGuard,
+    /// Maximum number of bytes accepted in a request body.
+    max_body_size: usize,
+    /// How long to wait for the full request body.
+    read_timeout: Duration,
+    /// Browser login via Discord, if configured.
+    oauth: Option<OAuth>,
 }
 
-impl App {
-    pub fn new(db: Database, id: NonZeroU64, token: String, public: VerifyingKey) -> Self {
-        Self { bot: Bot::new(db, id, token), public }
+impl<S: QuizStore> App<S> {
+    pub fn new(db: S, id: NonZeroU64, token: String, public: VerifyingKey) -> Self {
+        AppBuilder::new(id, token).build(db, public)
+    }
+
+    /// Ends every running quiz early and waits until all of them have announced their results.
+    pub async fn shutdown(&self) {
+        self.bot.shutdown().await;
     }
 
-    pub async fn try_respond(
+    /// Serves a request for `path`, which may include a query string.
+    pub async fn try_respond<B>(
         &self,
         response: &mut Response<Full<Bytes>>,
         method: Method,
         path: &str,
         headers: HeaderMap,
-        mut body: Incoming,
-    ) -> bool {
+        body: B,
+    ) -> bool
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: Display,
+    {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        if let Some(route) = path.strip_prefix("/oauth/") {
+            return self.respond_oauth(response, method, route, query, &headers).await;
+        }
+
+        if let Some(route) = path.strip_prefix("/api/v1").filter(|route| route.is_empty() || route.starts_with('/')) {
+            return self.respond_rest(response, method, route, &headers, body).await;
+        }
+
         match method {
             Method::GET | Method::HEAD => match path {
-                "/health" => {
-                    log::info!("health check pinged");
+                "/health" | "/health/live" => {
+                    tracing::info!("liveness check pinged");
+                    Self::write_json(response, StatusCode::OK, &json!({ "status": "ok" }));
+                    return true;
+                }
+                "/health/ready" => {
+                    tracing::info!("readiness check pinged");
+                    let (status, report) = self.readiness().await;
+                    Self::write_json(response, status, &report);
+                    return status.is_success();
+                }
+                "/metrics" => {
+                    use hyper::header::{HeaderValue, CONTENT_TYPE};
+                    tracing::debug!("metrics scraped");
+                    *response.body_mut() = self.metrics.render(self.bot.active_sessions()).into();
+                    let content_type = HeaderValue::from_static("text/plain; version=0.0.4");
+                    if let Some(value) = response.headers_mut().insert(CONTENT_TYPE, content_type) {
+                        tracing::warn!("existing header value: {value:?}");
+                    }
                     return true;
                 }
                 _ => {
-                    log::error!("unexpected `{method} {path}` request received");
+                    tracing::error!("unexpected `{method} {path}` request received");
                     *response.status_mut() = StatusCode::NOT_FOUND;
                     return false;
                 }
@@ -46,83 +131,163 @@ impl App {
             Method::POST => match path {
                 "/discord" => (),
                 _ => {
-                    log::error!("unexpected `POST {path}` request received");
+                    tracing::error!("unexpected `POST {path}` request received");
                     *response.status_mut() = StatusCode::NOT_FOUND;
                     return false;
                 }
             },
             _ => {
-                log::error!("unexpected `{method} {path}` request received");
+                tracing::error!("unexpected `{method} {path}` request received");
                 *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
                 return false;
             }
         }
 
-        log::debug!("new Discord interaction received");
+        tracing::debug!("new Discord interaction received");
 
         // Retrieve security headers
         let signature = headers.get("X-Signature-Ed25519");
         let timestamp = headers.get("X-Signature-Timestamp");
         let Some((signature, timestamp)) = signature.zip(timestamp) else {
-            log::error!("no signatures in headers");
+            tracing::error!("no signatures in headers");
             *response.status_mut() = StatusCode::UNAUTHORIZED;
             return false;
         };
 
         let mut buffer = [0; 64];
         if let Err(err) = hex::decode_to_slice(signature, &mut buffer) {
-            log::error!("bad signature hex encoding: {err}");
+            tracing::error!("bad signature hex encoding: {err}");
             *response.status_mut() = StatusCode::BAD_REQUEST;
             return false;
         }
 
         // Append body after the timestamp
-        use http_body_util::BodyExt;
         let mut message = timestamp.as_bytes().to_vec();
         let start = message.len();
-        while let Some(frame) = body.frame().await {
-            let frame = match frame {
-                Ok(frame) => frame,
-                Err(err) => {
-                    log::error!("body stream prematurely ended: {err}");
-                    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-                    return false;
-                }
-            };
-            if let Some(data) = frame.data_ref() {
-                message.extend_from_slice(data);
-            }
+        if let Err(status) = self.read_body(body, &mut message).await {
+            *response.status_mut() = status;
+            return false;
         }
 
-        log::debug!("fully received payload body");
+        tracing::debug!("fully received payload body");
 
         // Validate the challenge
         let signature = ed25519_dalek::Signature::from_bytes(&buffer);
         if let Err(err) = self.public.verify_strict(&message, &signature) {
-            log::error!("cannot verify message with signature: {err}");
+            tracing::error!("cannot verify message with signature: {err}");
+            self.metrics.record_signature_failure();
             *response.status_mut() = StatusCode::FORBIDDEN;
             return false;
         }
 
+        // The timestamp is covered by the signature, so it can be trusted from here on
+        let Some(timestamp) = timestamp.to_str().ok().and_then(|timestamp| timestamp.parse().ok()) else {
+            tracing::error!("malformed signature timestamp");
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            return false;
+        };
+
+        if !self.replay.is_fresh(timestamp) {
+            tracing::error!("stale signature timestamp {timestamp}");
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+            return false;
+        }
+
         let Some(payload) = message.get(start..) else {
-            log::error!("body is empty");
+            tracing::error!("body is empty");
             *response.status_mut() = StatusCode::BAD_REQUEST;
             return false;
         };
 
-        let reply = match serde_json::from_slice(payload) {
-            Ok(interaction) => self.bot.on_message(interaction).await,
+        let interaction: Interaction = match serde_json::from_slice(payload) {
+            Ok(interaction) => interaction,
             Err(err) => {
-                log::error!("body is not JSON-encoded: {err}");
+                tracing::error!("body is not JSON-encoded: {err}");
                 *response.status_mut() = StatusCode::BAD_REQUEST;
                 return false;
             }
         };
 
-        *response.body_mut() = match serde_json::to_vec(&reply) {
+        if !self.replay.insert(interaction.id.get()) {
+            tracing::error!("duplicate interaction {} received", interaction.id);
+            *response.status_mut() = StatusCode::CONFLICT;
+            return false;
+        }
+
+        let reply = self.bot.on_message(interaction).await;
+        Self::write_json(response, StatusCode::OK, &reply)
+    }
+
+    /// Appends the whole `body` to `buffer` within the configured size and time limits.
+    async fn read_body<B>(&self, mut body: B, buffer: &mut Vec<u8>) -> Result<(), StatusCode>
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: Display,
+    {
+        // Reject bodies that announce an oversized `Content-Length` without reading them
+        let limit = self.max_body_size;
+        let announced = body.size_hint().lower();
+        if announced > limit as u64 {
+            tracing::error!("announced body size {announced} exceeds limit {limit}");
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        use http_body_util::BodyExt;
+        let start = buffer.len();
+        let read = async {
+            while let Some(frame) = body.frame().await {
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        tracing::error!("body stream prematurely ended: {err}");
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                };
+                if let Some(data) = frame.data_ref() {
+                    if buffer.len() - start + data.len() > limit {
+                        tracing::error!("streamed body exceeds limit {limit}");
+                        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+                    }
+                    buffer.extend_from_slice(data);
+                }
+            }
+            Ok(())
+        };
+
+        match tokio::time::timeout(self.read_timeout, read).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::error!("body not received within {:?}", self.read_timeout);
+                Err(StatusCode::REQUEST_TIMEOUT)
+            }
+        }
+    }
+
+    /// Checks every dependency needed to serve interactions. Load balancers should stop routing traffic here
+    /// unless the status is `200 OK`.
+    async fn readiness(&self) -> (StatusCode, serde_json::Value) {
+        let start = Instant::now();
+        let result = self.bot.ping_database().await;
+        let latency = start.elapsed().as_secs_f64() * 1000.0;
+        let (status, summary, database) = match result {
+            Ok(()) => (StatusCode::OK, "ready", json!({ "status": "up", "latency_ms": latency })),
+            Err(err) => {
+                tracing::error!("database readiness check failed with `{err:?}`");
+                let database = json!({ "status": "down", "latency_ms": latency, "error": format!("{err:?}") });
+                (StatusCode::SERVICE_UNAVAILABLE, "unavailable", database)
+            }
+        };
+
+        let sessions = json!({ "status": "up", "active": self.bot.active_sessions() });
+        (status, json!({ "status": summary, "components": { "database": database, "sessions": sessions } }))
+    }
+
+    fn write_json(response: &mut Response<Full<Bytes>>, status: StatusCode, value: &impl serde::Serialize) -> bool {
+        *response.status_mut() = status;
+        *response.body_mut() = match serde_json::to_vec(value) {
             Ok(bytes) => bytes.into(),
             Err(err) => {
-                log::error!("cannot encode reply to JSON: {err}");
+                tracing::error!("cannot encode reply to JSON: {err}");
                 *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
                 return false;
             }
@@ -130,7 +295,7 @@ impl App {
 
         use hyper::header::{HeaderValue, CONTENT_TYPE};
         if let Some(value) = response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json")) {
-            log::warn!("existing header value: {value:?}");
+            tracing::warn!("existing header value: {value:?}");
         }
         true
     }
@@ -0,0 +1,316 @@
+//! Prometheus metrics in the text exposition format.
+
+use core::{
+    fmt::Write,
+    future::Future,
+    num::{NonZeroI16, NonZeroU64},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use dashmap::DashMap;
+use db::{error, DeadLetter, GuildSettings, Quiz, QuizPatch, QuizStore, RawQuiz, TryStream, Webhook};
+use std::{sync::Arc, time::Instant};
+
+/// Upper bounds (in seconds) of the latency histogram buckets.
+const BUCKETS: [f64; 11] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+#[derive(Default)]
+struct Histogram {
+    /// Non-cumulative count for each bucket in [`BUCKETS`].
+    buckets: [AtomicU64; BUCKETS.len()],
+    /// Total number of observations.
+    count: AtomicU64,
+    /// Sum of all observations in microseconds.
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if let Some(bucket) = BUCKETS.iter().position(|&bound| secs <= bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.sum.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    /// Interactions keyed by type and command name.
+    interactions: DashMap<(&'static str, &'static str), AtomicU64>,
+    /// REST API and OAuth2 requests keyed by endpoint.
+    rest_requests: DashMap<&'static str, AtomicU64>,
+    /// Failed interactions keyed by the error variant.
+    errors: DashMap<&'static str, AtomicU64>,
+    /// Requests whose Ed25519 signature could not be verified.
+    signature_failures: AtomicU64,
+    /// Database query latencies keyed by the store method.
+    queries: DashMap<&'static str, Histogram>,
+    /// Quiz results that could not be announced.
+    followup_failures: AtomicU64,
+    /// Webhook payloads that ended up in the dead-letter table.
+    webhook_failures: AtomicU64,
+}
+
+impl Metrics {
+    pub fn record_interaction(&self, kind: &'static str, command: &'static str) {
+        self.interactions.entry((kind, command)).or_default().fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rest_request(&self, endpoint: &'static str) {
+        self.rest_requests.entry(endpoint).or_default().fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self, variant: &'static str) {
+        self.errors.entry(variant).or_default().fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_signature_failure(&self) {
+        self.signature_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_query(&self, method: &'static str, elapsed: Duration) {
+        self.queries.entry(method).or_default().observe(elapsed);
+    }
+
+    pub fn record_followup_failure(&self) {
+        self.followup_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_webhook_failure(&self) {
+        self.webhook_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all metrics alongside the current number of `active_sessions`.
+    pub fn render(&self, active_sessions: usize) -> String {
+        let mut out = String::new();
+
+        out += "# HELP quizzo_interactions_total Interactions received by type and command.\n";
+        out += "# TYPE quizzo_interactions_total counter\n";
+        let mut interactions: Vec<_> =
+            self.interactions.iter().map(|entry| (*entry.key(), entry.value().load(Ordering::Relaxed))).collect();
+        interactions.sort_unstable();
+        for ((kind, command), count) in interactions {
+            writeln!(out, "quizzo_interactions_total{{type=\"{kind}\",command=\"{command}\"}} {count}").unwrap();
+        }
+
+        out += "# HELP quizzo_rest_requests_total REST API and OAuth2 requests by endpoint.\n";
+        out += "# TYPE quizzo_rest_requests_total counter\n";
+        let mut requests: Vec<_> =
+            self.rest_requests.iter().map(|entry| (*entry.key(), entry.value().load(Ordering::Relaxed))).collect();
+        requests.sort_unstable();
+        for (endpoint, count) in requests {
+            writeln!(out, "quizzo_rest_requests_total{{endpoint=\"{endpoint}\"}} {count}").unwrap();
+        }
+
+        out += "# HELP quizzo_errors_total Failed interactions by error variant.\n";
+        out += "# TYPE quizzo_errors_total counter\n";
+        let mut errors: Vec<_> =
+            self.errors.iter().map(|entry| (*entry.key(), entry.value().load(Ordering::Relaxed))).collect();
+        errors.sort_unstable();
+        for (variant, count) in errors {
+            writeln!(out, "quizzo_errors_total{{variant=\"{variant}\"}} {count}").unwrap();
+        }
+
+        out += "# HELP quizzo_signature_failures_total Requests with an invalid Ed25519 signature.\n";
+        out += "# TYPE quizzo_signature_failures_total counter\n";
+        let failures = self.signature_failures.load(Ordering::Relaxed);
+        writeln!(out, "quizzo_signature_failures_total {failures}").unwrap();
+
+        out += "# HELP quizzo_db_query_duration_seconds Latency of database queries by store method.\n";
+        out += "# TYPE quizzo_db_query_duration_seconds histogram\n";
+        let mut methods: Vec<_> = self.queries.iter().map(|entry| *entry.key()).collect();
+        methods.sort_unstable();
+        for method in methods {
+            let Some(histogram) = self.queries.get(method) else {
+                continue;
+            };
+            let mut cumulative = 0;
+            for (bound, bucket) in BUCKETS.iter().zip(&histogram.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                writeln!(
+                    out,
+                    "quizzo_db_query_duration_seconds_bucket{{method=\"{method}\",le=\"{bound}\"}} {cumulative}"
+                )
+                .unwrap();
+            }
+            let count = histogram.count.load(Ordering::Relaxed);
+            let sum = Duration::from_micros(histogram.sum.load(Ordering::Relaxed)).as_secs_f64();
+            writeln!(out, "quizzo_db_query_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} {count}")
+                .unwrap();
+            writeln!(out, "quizzo_db_query_duration_seconds_sum{{method=\"{method}\"}} {sum}").unwrap();
+            writeln!(out, "quizzo_db_query_duration_seconds_count{{method=\"{method}\"}} {count}").unwrap();
+        }
+
+        out += "# HELP quizzo_active_sessions Quizzes currently accepting answers.\n";
+        out += "# TYPE quizzo_active_sessions gauge\n";
+        writeln!(out, "quizzo_active_sessions {active_sessions}").unwrap();
+
+        out += "# HELP quizzo_followup_failures_total Quiz results that could not be delivered.\n";
+        out += "# TYPE quizzo_followup_failures_total counter\n";
+        let failures = self.followup_failures.load(Ordering::Relaxed);
+        writeln!(out, "quizzo_followup_failures_total {failures}").unwrap();
+
+        out += "# HELP quizzo_webhook_failures_total Webhook payloads that were dead-lettered.\n";
+        out += "# TYPE quizzo_webhook_failures_total counter\n";
+        let failures = self.webhook_failures.load(Ordering::Relaxed);
+        writeln!(out, "quizzo_webhook_failures_total {failures}").unwrap();
+
+        out
+    }
+}
+
+/// A [`QuizStore`] that records the latency of every query made to the wrapped store.
+pub struct Instrumented<S> {
+    store: S,
+    metrics: Arc<Metrics>,
+}
+
+impl<S> Instrumented<S> {
+    pub fn new(store: S, metrics: Arc<Metrics>) -> Self {
+        Self { store, metrics }
+    }
+
+    async fn time<T>(&self, method: &'static str, query: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let result = query.await;
+        self.metrics.record_query(method, start.elapsed());
+        result
+    }
+}
+
+impl<S: QuizStore> QuizStore for Instrumented<S> {
+    async fn ping(&self) -> error::Result<()> {
+        self.time("ping", self.store.ping()).await
+    }
+
+    async fn init_quiz(
+        &self,
+        user: NonZeroU64,
+        guild: Option<NonZeroU64>,
+        question: &str,
+        expiration: u16,
+    ) -> error::Result<NonZeroI16> {
+        self.time("init_quiz", self.store.init_quiz(user, guild, question, expiration)).await
+    }
+
+    async fn get_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        self.time("get_quiz", self.store.get_quiz(user, quiz)).await
+    }
+
+    /// Only the time until the first row is available is recorded.
+    async fn get_quizzes_by_user(
+        &self,
+        user: NonZeroU64,
+    ) -> error::Result<impl TryStream<Ok = Quiz, Error = error::Error> + Send + '_> {
+        self.time("get_quizzes_by_user", self.store.get_quizzes_by_user(user)).await
+    }
+
+    async fn pop_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        self.time("pop_quiz", self.store.pop_quiz(user, quiz)).await
+    }
+
+    async fn remove_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        self.time("remove_quiz", self.store.remove_quiz(user, quiz)).await
+    }
+
+    async fn add_choice(&self, user: NonZeroU64, quiz: NonZeroI16, choice: &str) -> error::Result<()> {
+        self.time("add_choice", self.store.add_choice(user, quiz, choice)).await
+    }
+
+    async fn remove_choice(&self, user: NonZeroU64, quiz: NonZeroI16, index: u32) -> error::Result<Box<str>> {
+        self.time("remove_choice", self.store.remove_choice(user, quiz, index)).await
+    }
+
+    async fn set_question(&self, user: NonZeroU64, quiz: NonZeroI16, question: &str) -> error::Result<()> {
+        self.time("set_question", self.store.set_question(user, quiz, question)).await
+    }
+
+    async fn set_answer(&self, user: NonZeroU64, quiz: NonZeroI16, answer: u16) -> error::Result<()> {
+        self.time("set_answer", self.store.set_answer(user, quiz, answer)).await
+    }
+
+    async fn set_expiration(&self, user: NonZeroU64, quiz: NonZeroI16, expiration: u16) -> error::Result<()> {
+        self.time("set_expiration", self.store.set_expiration(user, quiz, expiration)).await
+    }
+
+    async fn update_quiz(&self, user: NonZeroU64, quiz: NonZeroI16, patch: &QuizPatch) -> error::Result<()> {
+        self.time("update_quiz", self.store.update_quiz(user, quiz, patch)).await
+    }
+
+    async fn set_api_key(&self, user: NonZeroU64, digest: [u8; 32]) -> error::Result<()> {
+        self.time("set_api_key", self.store.set_api_key(user, digest)).await
+    }
+
+    async fn remove_api_key(&self, user: NonZeroU64) -> error::Result<()> {
+        self.time("remove_api_key", self.store.remove_api_key(user)).await
+    }
+
+    async fn get_user_by_api_key(&self, digest: [u8; 32]) -> error::Result<NonZeroU64> {
+        self.time("get_user_by_api_key", self.store.get_user_by_api_key(digest)).await
+    }
+
+    async fn set_webhook(&self, guild: NonZeroU64, url: &str, secret: &str) -> error::Result<()> {
+        self.time("set_webhook", self.store.set_webhook(guild, url, secret)).await
+    }
+
+    async fn remove_webhook(&self, guild: NonZeroU64, url: &str) -> error::Result<()> {
+        self.time("remove_webhook", self.store.remove_webhook(guild, url)).await
+    }
+
+    async fn get_webhooks(&self, guild: NonZeroU64) -> error::Result<Vec<Webhook>> {
+        self.time("get_webhooks", self.store.get_webhooks(guild)).await
+    }
+
+    async fn add_dead_letter(&self, guild: NonZeroU64, letter: &DeadLetter) -> error::Result<()> {
+        self.time("add_dead_letter", self.store.add_dead_letter(guild, letter)).await
+    }
+
+    async fn get_dead_letters(&self, guild: NonZeroU64) -> error::Result<Vec<DeadLetter>> {
+        self.time("get_dead_letters", self.store.get_dead_letters(guild)).await
+    }
+
+    async fn get_guild_settings(&self, guild: NonZeroU64) -> error::Result<GuildSettings> {
+        self.time("get_guild_settings", self.store.get_guild_settings(guild)).await
+    }
+
+    async fn set_guild_settings(&self, guild: NonZeroU64, settings: &GuildSettings) -> error::Result<()> {
+        self.time("set_guild_settings", self.store.set_guild_settings(guild, settings)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Duration, Metrics};
+
+    #[test]
+    fn render_text_format() {
+        let metrics = Metrics::default();
+        metrics.record_interaction("application_command", "create");
+        metrics.record_interaction("application_command", "create");
+        metrics.record_interaction("ping", "");
+        metrics.record_rest_request("list_quizzes");
+        metrics.record_error("NotFound");
+        metrics.record_signature_failure();
+        metrics.record_query("init_quiz", Duration::from_millis(3));
+        metrics.record_query("init_quiz", Duration::from_secs(10));
+
+        let text = metrics.render(2);
+        assert!(text.contains("quizzo_interactions_total{type=\"application_command\",command=\"create\"} 2\n"));
+        assert!(text.contains("quizzo_interactions_total{type=\"ping\",command=\"\"} 1\n"));
+        assert!(text.contains("quizzo_rest_requests_total{endpoint=\"list_quizzes\"} 1\n"));
+        assert!(!text.contains("type=\"rest\""));
+        assert!(text.contains("quizzo_errors_total{variant=\"NotFound\"} 1\n"));
+        assert!(text.contains("quizzo_signature_failures_total 1\n"));
+        assert!(text.contains("quizzo_db_query_duration_seconds_bucket{method=\"init_quiz\",le=\"0.001\"} 0\n"));
+        assert!(text.contains("quizzo_db_query_duration_seconds_bucket{method=\"init_quiz\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("quizzo_db_query_duration_seconds_bucket{method=\"init_quiz\",le=\"5\"} 1\n"));
+        assert!(text.contains("quizzo_db_query_duration_seconds_bucket{method=\"init_quiz\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("quizzo_db_query_duration_seconds_count{method=\"init_quiz\"} 2\n"));
+        assert!(text.contains("quizzo_active_sessions 2\n"));
+        assert!(text.contains("quizzo_followup_failures_total 0\n"));
+        assert!(text.contains("quizzo_webhook_failures_total 0\n"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Discord OAuth2 login so that browsers can use the REST API with a session cookie instead of an API key.
+
+use crate::{App, HttpsClient};
+use core::{num::NonZeroU64, time::Duration};
+use dashmap::DashMap;
+use db::QuizStore;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Bytes,
+    header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE},
+    HeaderMap, Method, Request, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+
+/// Identifies the logged-in user on every subsequent request.
+const SESSION_COOKIE: &str = "quizzo_session";
+/// Ties the callback to the browser that started the login.
+const STATE_COOKIE: &str = "quizzo_oauth_state";
+/// How long a session stays valid, matching the lifetime of Discord access tokens.
+const SESSION_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// How long the user may take to authorize the application.
+const STATE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Upper bound for each request to the Discord API.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Application credentials for the authorization-code flow.
+///
+/// Sessions only live in a process-local map, so a restart logs everyone out and replicas behind a load balancer do
+/// not share them.
+#[derive(Clone)]
+pub struct OAuthConfig {
+    pub client_secret: String,
+    /// Must exactly match one of the redirects registered in the Developer Portal and point to `/oauth/callback`.
+    pub redirect_uri: String,
+}
+
+struct Session {
+    user: NonZeroU64,
+    expires: Instant,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct User {
+    id: String,
+}
+
+pub struct OAuth {
+    client_id: NonZeroU64,
+    config: OAuthConfig,
+    /// Scheme and host of the Discord API, such as `https://discord.com`.
+    origin: String,
+    http: HttpsClient,
+    /// Live sessions keyed by the SHA-256 digest of their cookie.
+    sessions: DashMap<[u8; 32], Session>,
+}
+
+impl OAuth {
+    pub fn new(client_id: NonZeroU64, config: OAuthConfig, origin: String, http: HttpsClient) -> Self {
+        Self { client_id, config, origin, http, sessions: DashMap::new() }
+    }
+
+    /// Whether cookies may only be sent over HTTPS, which is the case unless the callback is plaintext.
+    fn secure(&self) -> &'static str {
+        if self.config.redirect_uri.starts_with("https://") {
+            "; Secure"
+        } else {
+            ""
+        }
+    }
+
+    /// Where to send the browser to ask the user for consent.
+    fn authorize_url(&self, state: &str) -> String {
+        let id = self.client_id.to_string();
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("response_type", "code")
+            .append_pair("scope", "identify")
+            .append_pair("client_id", &id)
+            .append_pair("state", state)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .finish();
+        format!("{}/oauth2/authorize?{query}", self.origin)
+    }
+
+    /// Trades the authorization `code` for an access token, which then resolves the user.
+    async fn exchange(&self, code: &str) -> Result<NonZeroU64, String> {
+        let id = self.client_id.to_string();
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_uri),
+            ("client_id", &id),
+            ("client_secret", &self.config.client_secret),
+        ];
+        let form = form_urlencoded::Serializer::new(String::new()).extend_pairs(form).finish();
+        let request = Request::post(format!("{}/api/v10/oauth2/token", self.origin))
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Full::from(form))
+            .map_err(|err| err.to_string())?;
+        let Token { access_token } = self.send(request).await?;
+
+        let request = Request::get(format!("{}/api/v10/users/@me", self.origin))
+            .header(AUTHORIZATION, format!("Bearer {access_token}"))
+            .body(Full::default())
+            .map_err(|err| err.to_string())?;
+        let User { id } = self.send(request).await?;
+        id.parse().map_err(|_| format!("malformed user ID `{id}`"))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: Request<Full<Bytes>>) -> Result<T, String> {
+        let path = request.uri().path().to_owned();
+        let exchange = async {
+            let response = self.http.request(request).await.map_err(|err| err.to_string())?;
+            let status = response.status();
+            let body = response.into_body().collect().await.map_err(|err| err.to_string())?.to_bytes();
+            if !status.is_success() {
+                return Err(format!("status {status}: {}", String::from_utf8_lossy(&body)));
+            }
+            serde_json::from_slice(&body).map_err(|err| format!("unexpected body: {err}"))
+        };
+        match tokio::time::timeout(REQUEST_TIMEOUT, exchange).await {
+            Ok(result) => result.map_err(|err| format!("`{path}` failed with {err}")),
+            Err(_) => Err(format!("`{path}` timed out after {REQUEST_TIMEOUT:?}")),
+        }
+    }
+
+    /// Creates a session for `user` and returns its cookie value. Expired sessions are pruned along the way.
+    fn start_session(&self, user: NonZeroU64) -> Result<String, getrandom::Error> {
+        let token = random_token()?;
+        let now = Instant::now();
+        self.sessions.retain(|_, session| session.expires > now);
+        self.sessions.insert(digest(&token), Session { user, expires: now + SESSION_TTL });
+        Ok(token)
+    }
+
+    /// Resolves the user behind the session cookie, if any.
+    pub fn session(&self, headers: &HeaderMap) -> Option<NonZeroU64> {
+        let token = cookie(headers, SESSION_COOKIE)?;
+        let session = self.sessions.get(&digest(token))?;
+        (session.expires > Instant::now()).then_some(session.user)
+    }
+
+    fn end_session(&self, headers: &HeaderMap) {
+        if let Some(token) = cookie(headers, SESSION_COOKIE) {
+            self.sessions.remove(&digest(token));
+        }
+    }
+
+    fn set_cookie(&self, response: &mut Response<Full<Bytes>>, name: &str, value: &str, path: &str, ttl: Duration) {
+        let secure = self.secure();
+        let cookie = format!("{name}={value}; Path={path}; Max-Age={}; HttpOnly; SameSite=Lax{secure}", ttl.as_secs());
+        match HeaderValue::from_str(&cookie) {
+            Ok(value) => {
+                response.headers_mut().append(SET_COOKIE, value);
+            }
+            Err(err) => tracing::error!("cannot set cookie `{name}`: {err}"),
+        }
+    }
+}
+
+impl<S: QuizStore> App<S> {
+    /// Serves `route` (relative to `/oauth/`) of the authorization-code flow.
+    pub(crate) async fn respond_oauth(
+        &self,
+        response: &mut Response<Full<Bytes>>,
+        method: Method,
+        route: &str,
+        query: &str,
+        headers: &HeaderMap,
+    ) -> bool {
+        let Some(oauth) = &self.oauth else {
+            tracing::error!("OAuth2 login requested but not configured");
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            return false;
+        };
+
+        match (method, route) {
+            (Method::GET, "login") => {
+                self.metrics.record_rest_request("oauth_login");
+                Self::login(oauth, response)
+            }
+            (Method::GET, "callback") => {
+                self.metrics.record_rest_request("oauth_callback");
+                Self::callback(oauth, response, query, headers).await
+            }
+            (Method::POST, "logout") => {
+                self.metrics.record_rest_request("oauth_logout");
+                oauth.end_session(headers);
+                oauth.set_cookie(response, SESSION_COOKIE, "", "/", Duration::ZERO);
+                *response.status_mut() = StatusCode::NO_CONTENT;
+                true
+            }
+            (method, "login" | "callback" | "logout") => {
+                tracing::error!("unexpected `{method} /oauth/{route}` request received");
+                *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+                false
+            }
+            (method, _) => {
+                tracing::error!("unexpected `{method} /oauth/{route}` request received");
+                *response.status_mut() = StatusCode::NOT_FOUND;
+                false
+            }
+        }
+    }
+
+    /// Redirects to the Discord consent screen.
+    fn login(oauth: &OAuth, response: &mut Response<Full<Bytes>>) -> bool {
+        let state = match random_token() {
+            Ok(state) => state,
+            Err(err) => {
+                tracing::error!("cannot generate OAuth2 state: {err}");
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                return false;
+            }
+        };
+
+        let location = match HeaderValue::try_from(oauth.authorize_url(&state)) {
+            Ok(location) => location,
+            Err(err) => {
+                tracing::error!("invalid authorization URL: {err}");
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                return false;
+            }
+        };
+
+        oauth.set_cookie(response, STATE_COOKIE, &state, "/oauth", STATE_TTL);
+        response.headers_mut().insert(LOCATION, location);
+        *response.status_mut() = StatusCode::FOUND;
+        true
+    }
+
+    /// Completes the login that Discord redirected back with.
+    async fn callback(oauth: &OAuth, response: &mut Response<Full<Bytes>>, query: &str, headers: &HeaderMap) -> bool {
+        if let Some(error) = query_param(query, "error") {
+            tracing::error!("OAuth2 authorization denied: {error}");
+            Self::write_json(response, StatusCode::FORBIDDEN, &json!({ "error": "authorization denied" }));
+            return false;
+        }
+
+        // Reject callbacks that were not started by this browser (i.e., login CSRF)
+        let expected = cookie(headers, STATE_COOKIE);
+        let state = query_param(query, "state");
+        let Some(code) = query_param(query, "code").filter(|_| expected.is_some() && state.as_deref() == expected)
+        else {
+            tracing::error!("OAuth2 callback without a code or with a mismatched state");
+            Self::write_json(
+                response,
+                StatusCode::BAD_REQUEST,
+                &json!({ "error": "invalid or expired login attempt" }),
+            );
+            return false;
+        };
+        oauth.set_cookie(response, STATE_COOKIE, "", "/oauth", Duration::ZERO);
+
+        let user = match oauth.exchange(&code).await {
+            Ok(user) => user,
+            Err(err) => {
+                tracing::error!("cannot complete OAuth2 login: {err}");
+                Self::write_json(response, StatusCode::BAD_GATEWAY, &json!({ "error": "cannot reach Discord" }));
+                return false;
+            }
+        };
+
+        let token = match oauth.start_session(user) {
+            Ok(token) => token,
+            Err(err) => {
+                tracing::error!("cannot generate session token: {err}");
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                return false;
+            }
+        };
+
+        tracing::info!("user {user} logged in via OAuth2");
+        oauth.set_cookie(response, SESSION_COOKIE, &token, "/", SESSION_TTL);
+        Self::write_json(response, StatusCode::OK, &json!({ "user": user.to_string() }))
+    }
+}
+
+fn random_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0; 32];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+/// Finds the value of the cookie called `name`.
+fn cookie<'h>(headers: &'h HeaderMap, name: &str) -> Option<&'h str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(name)?.strip_prefix('='))
+        .filter(|value| !value.is_empty())
+}
+
+/// Finds and decodes the value of the query parameter called `name`.
+fn query_param(query: &str, name: &str) -> Option<String> {
+    form_urlencoded::parse(query.as_bytes()).find(|(key, _)| key == name).map(|(_, value)| value.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cookie, query_param};
+    use hyper::{
+        header::{HeaderValue, COOKIE},
+        HeaderMap,
+    };
+
+    #[test]
+    fn url_and_cookie_helpers() {
+        assert_eq!(query_param("error=a%2Fb+c%zz", "error").as_deref(), Some("a/b c%zz"));
+        assert_eq!(query_param("code=abc%3D&state=xyz", "code").as_deref(), Some("abc="));
+        assert_eq!(query_param("xcode=1", "code"), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark; quizzo_session=abc; x_quizzo_session=def"));
+        assert_eq!(cookie(&headers, "quizzo_session"), Some("abc"));
+        assert_eq!(cookie(&headers, "quizzo"), None);
+    }
+}
@@ -0,0 +1,91 @@
+//! Protection against replayed interaction requests.
+
+use core::time::Duration;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Default)]
+struct Seen {
+    /// Interaction IDs for fast lookups.
+    ids: HashSet<u64>,
+    /// Interaction IDs in insertion order for eviction.
+    order: VecDeque<u64>,
+}
+
+pub struct ReplayGuard {
+    /// Maximum allowed difference between the signed timestamp and the current time.
+    window: Duration,
+    /// Maximum number of interaction IDs to remember. Zero disables duplicate detection.
+    capacity: usize,
+    seen: Mutex<Seen>,
+}
+
+impl ReplayGuard {
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(5 * 60);
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self { window, capacity, seen: Mutex::default() }
+    }
+
+    /// Checks whether the UNIX `timestamp` (in seconds) lies within the window around the current time.
+    pub fn is_fresh(&self, timestamp: u64) -> bool {
+        let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+            return false;
+        };
+        now.as_secs().abs_diff(timestamp) <= self.window.as_secs()
+    }
+
+    /// Remembers the interaction ID. Returns `false` if it has been seen recently.
+    pub fn insert(&self, id: u64) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        if !seen.ids.insert(id) {
+            return false;
+        }
+
+        seen.order.push_back(id);
+        while seen.order.len() > self.capacity {
+            let Some(oldest) = seen.order.pop_front() else {
+                break;
+            };
+            seen.ids.remove(&oldest);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Duration, ReplayGuard, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn duplicates_are_detected_until_evicted() {
+        let guard = ReplayGuard::new(Duration::from_secs(60), 2);
+        assert!(guard.insert(1));
+        assert!(!guard.insert(1));
+        assert!(guard.insert(2));
+        assert!(guard.insert(3));
+        assert!(guard.insert(1));
+        assert!(!guard.insert(3));
+    }
+
+    #[test]
+    fn timestamps_must_be_recent() {
+        let guard = ReplayGuard::new(Duration::from_secs(60), 0);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(guard.is_fresh(now));
+        assert!(guard.is_fresh(now - 30));
+        assert!(guard.is_fresh(now + 30));
+        assert!(!guard.is_fresh(now - 120));
+        assert!(!guard.is_fresh(now + 120));
+        assert!(!guard.is_fresh(0));
+    }
+}
@@ -0,0 +1,241 @@
+//! JSON REST API under `/api/v1` for managing quizzes outside of Discord.
+
+use crate::App;
+use core::{fmt::Display, num::NonZeroI16, num::NonZeroU64};
+use db::{
+    error::{Constraint, Error},
+    Quiz, QuizPatch, QuizStore, TryStreamExt,
+};
+use http_body_util::Full;
+use hyper::{
+    body::{Body, Bytes},
+    header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE},
+    HeaderMap, Method, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// Makes leaked keys easy to recognize (e.g., by secret scanners).
+const KEY_PREFIX: &str = "quizzo_";
+
+/// A bearer token for the REST API. Only its SHA-256 digest is ever stored.
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn generate() -> Result<Self, getrandom::Error> {
+        let mut bytes = [0; 32];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(Self(format!("{KEY_PREFIX}{}", hex::encode(bytes))))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn digest(&self) -> [u8; 32] {
+        digest(&self.0)
+    }
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// A failed request, reported as `{ "error": message }`.
+struct Problem {
+    status: StatusCode,
+    message: String,
+}
+
+impl Problem {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+impl From<Error> for Problem {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NotFound => Self::new(StatusCode::NOT_FOUND, "quiz not found"),
+            Error::BadInput(constraint @ Constraint::ChoiceCount) => {
+                Self::new(StatusCode::CONFLICT, constraint.to_string())
+            }
+            Error::BadInput(constraint) => Self::new(StatusCode::BAD_REQUEST, constraint.to_string()),
+            Error::QuotaExceeded { limit, .. } => {
+                Self::new(StatusCode::CONFLICT, format!("quota of {limit} stored quizzes reached, start one first"))
+            }
+            Error::Fatal => Self::new(StatusCode::INTERNAL_SERVER_ERROR, "unexpected error"),
+        }
+    }
+}
+
+type Reply = Result<(StatusCode, Value), Problem>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewQuiz {
+    question: String,
+    /// Falls back to the configured default.
+    expiration: Option<u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QuizUpdate {
+    question: Option<String>,
+    expiration: Option<u16>,
+    answer: Option<u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewChoice {
+    choice: String,
+}
+
+fn parse_quiz(id: &str) -> Result<NonZeroI16, Problem> {
+    id.parse().map_err(|_| Error::NotFound.into())
+}
+
+fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, Problem> {
+    serde_json::from_slice(body).map_err(|err| Problem::new(StatusCode::BAD_REQUEST, format!("invalid body: {err}")))
+}
+
+impl<S: QuizStore> App<S> {
+    /// Serves `route` (relative to `/api/v1`) on behalf of the owner of the bearer API key or login session.
+    pub(crate) async fn respond_rest<B>(
+        &self,
+        response: &mut Response<Full<Bytes>>,
+        method: Method,
+        route: &str,
+        headers: &HeaderMap,
+        body: B,
+    ) -> bool
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: Display,
+    {
+        let user = match self.authenticate(headers).await {
+            Ok(user) => user,
+            Err(problem) => {
+                let challenge = HeaderValue::from_static("Bearer");
+                response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+                Self::write_json(response, problem.status, &json!({ "error": problem.message }));
+                return false;
+            }
+        };
+
+        let mut bytes = Vec::new();
+        if let Err(status) = self.read_body(body, &mut bytes).await {
+            *response.status_mut() = status;
+            return false;
+        }
+
+        let (status, value) = match self.route(user, &method, route, &bytes).await {
+            Ok(reply) => reply,
+            Err(Problem { status, message }) => {
+                tracing::warn!(%status, "REST request `{method} {route}` failed: {message}");
+                (status, json!({ "error": message }))
+            }
+        };
+        Self::write_json(response, status, &value) && status.is_success()
+    }
+
+    async fn authenticate(&self, headers: &HeaderMap) -> Result<NonZeroU64, Problem> {
+        let Some(key) = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            if let Some(user) = self.oauth.as_ref().and_then(|oauth| oauth.session(headers)) {
+                return Ok(user);
+            }
+            tracing::error!("no bearer token or session in headers");
+            return Err(Problem::new(StatusCode::UNAUTHORIZED, "missing bearer API key"));
+        };
+
+        match self.bot.store().get_user_by_api_key(digest(key.trim())).await {
+            Ok(user) => Ok(user),
+            Err(Error::NotFound) => {
+                tracing::error!("unknown API key");
+                Err(Problem::new(StatusCode::UNAUTHORIZED, "invalid API key"))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn route(&self, user: NonZeroU64, method: &Method, route: &str, body: &[u8]) -> Reply {
+        let segments: Vec<_> = route.trim_matches('/').split('/').collect();
+        let (name, reply) = match (method, segments.as_slice()) {
+            (&Method::GET, ["quizzes"]) => ("list_quizzes", self.list_quizzes(user).await),
+            (&Method::POST, ["quizzes"]) => ("create_quiz", self.create_quiz(user, parse_json(body)?).await),
+            (&Method::GET, ["quizzes", quiz]) => ("get_quiz", self.quiz(user, parse_quiz(quiz)?, StatusCode::OK).await),
+            (&Method::PATCH, ["quizzes", quiz]) => {
+                ("update_quiz", self.update_quiz(user, parse_quiz(quiz)?, parse_json(body)?).await)
+            }
+            (&Method::DELETE, ["quizzes", quiz]) => ("delete_quiz", self.delete_quiz(user, parse_quiz(quiz)?).await),
+            (&Method::POST, ["quizzes", quiz, "choices"]) => {
+                ("add_choice", self.add_choice(user, parse_quiz(quiz)?, parse_json(body)?).await)
+            }
+            (&Method::DELETE, ["quizzes", quiz, "choices", index]) => {
+                let index = index.parse().map_err(|_| Problem::new(StatusCode::NOT_FOUND, "choice not found"))?;
+                ("remove_choice", self.remove_choice(user, parse_quiz(quiz)?, index).await)
+            }
+            (_, ["quizzes"] | ["quizzes", _] | ["quizzes", _, "choices"] | ["quizzes", _, "choices", _]) => {
+                return Err(Problem::new(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"));
+            }
+            _ => return Err(Problem::new(StatusCode::NOT_FOUND, "no such endpoint")),
+        };
+        self.metrics.record_rest_request(name);
+        reply
+    }
+
+    /// Replies with the current state of the quiz.
+    async fn quiz(&self, user: NonZeroU64, quiz: NonZeroI16, status: StatusCode) -> Reply {
+        let raw = self.bot.store().get_quiz(user, quiz).await?;
+        Ok((status, json!(Quiz { id: quiz, raw })))
+    }
+
+    async fn list_quizzes(&self, user: NonZeroU64) -> Reply {
+        let quizzes: Vec<_> = self.bot.store().get_quizzes_by_user(user).await?.try_collect().await?;
+        Ok((StatusCode::OK, json!({ "quizzes": quizzes })))
+    }
+
+    /// Quizzes created over REST belong to no guild (like those created in DMs), so only the per-user quota applies.
+    async fn create_quiz(&self, user: NonZeroU64, NewQuiz { question, expiration }: NewQuiz) -> Reply {
+        let expiration = expiration.unwrap_or(self.bot.options().default_expiration);
+        let quiz = self.bot.store().init_quiz(user, None, &question, expiration).await?;
+        self.quiz(user, quiz, StatusCode::CREATED).await
+    }
+
+    /// Either every field is updated or, if any of them is rejected, none at all.
+    async fn update_quiz(&self, user: NonZeroU64, quiz: NonZeroI16, update: QuizUpdate) -> Reply {
+        let QuizUpdate { question, expiration, answer } = update;
+        self.bot.store().update_quiz(user, quiz, &QuizPatch { question, expiration, answer }).await?;
+        self.quiz(user, quiz, StatusCode::OK).await
+    }
+
+    /// Drafts without an answer may be removed as well, unlike when quizzes are started.
+    async fn delete_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> Reply {
+        let raw = self.bot.store().remove_quiz(user, quiz).await?;
+        Ok((StatusCode::OK, json!(Quiz { id: quiz, raw })))
+    }
+
+    async fn add_choice(&self, user: NonZeroU64, quiz: NonZeroI16, NewChoice { choice }: NewChoice) -> Reply {
+        self.bot.store().add_choice(user, quiz, &choice).await?;
+        self.quiz(user, quiz, StatusCode::CREATED).await
+    }
+
+    async fn remove_choice(&self, user: NonZeroU64, quiz: NonZeroI16, index: u32) -> Reply {
+        let choice = self.bot.store().remove_choice(user, quiz, index).await?;
+        Ok((StatusCode::OK, json!({ "choice": choice })))
+    }
+
+    /// Issues a new REST API key for `user`, which revokes the previous one.
+    pub async fn issue_api_key(&self, user: NonZeroU64) -> db::error::Result<ApiKey> {
+        let key = ApiKey::generate().map_err(|_| Error::Fatal)?;
+        self.bot.store().set_api_key(user, key.digest()).await?;
+        Ok(key)
+    }
+}
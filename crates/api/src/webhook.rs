@@ -0,0 +1,220 @@
+//! Signed notifications to external systems (e.g., a learning management system) whenever a quiz finishes.
+
+use crate::{metrics::Metrics, App, HttpsClient};
+use core::{num::NonZeroU64, time::Duration};
+use db::{DeadLetter, QuizStore, Webhook};
+use hmac::{Hmac, Mac};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Bytes,
+    header::{CONTENT_TYPE, USER_AGENT},
+    Request,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Header with the UNIX timestamp (in seconds) at which the payload was signed.
+const TIMESTAMP_HEADER: &str = "X-Quizzo-Timestamp";
+/// Header with the signature computed by [`sign_webhook`].
+const SIGNATURE_HEADER: &str = "X-Quizzo-Signature";
+/// Upper bound for each delivery attempt.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Generates a random secret for signing the payloads of a new webhook.
+pub fn webhook_secret() -> Result<String, getrandom::Error> {
+    let mut bytes = [0; 32];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+/// Computes the `X-Quizzo-Signature` header: the hex-encoded HMAC-SHA256 of `{timestamp}.{body}`, prefixed with
+/// `sha256=`. Receivers should recompute it with their secret and reject stale timestamps.
+pub fn sign_webhook(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// The final answer of one participant.
+#[derive(Serialize)]
+pub struct Answer {
+    pub user_id: String,
+    /// Zero-based index of the selected choice.
+    pub choice: u32,
+    pub correct: bool,
+    /// Time from the start of the quiz until the answer was (last) changed.
+    pub elapsed_ms: u64,
+}
+
+/// Payload of the `quiz.finished` event. Snowflakes are strings like in the Discord API.
+#[derive(Serialize)]
+pub struct Finished<'a> {
+    pub event: &'static str,
+    pub guild_id: String,
+    pub channel_id: Option<String>,
+    /// ID of the interaction that started the quiz, which identifies this run.
+    pub session_id: String,
+    pub quiz_id: i16,
+    pub author_id: String,
+    pub question: &'a str,
+    pub choices: &'a [String],
+    /// Zero-based index of the correct choice.
+    pub answer: u32,
+    pub started_at: u64,
+    pub ended_at: u64,
+    /// Whether the quiz was cut short by a shutdown.
+    pub ended_early: bool,
+    pub responses: Vec<Answer>,
+}
+
+impl Finished<'_> {
+    pub const EVENT: &'static str = "quiz.finished";
+}
+
+/// Why a delivery attempt failed.
+struct Failure {
+    /// Whether another attempt may succeed.
+    transient: bool,
+    message: String,
+}
+
+pub struct Webhooks {
+    http: HttpsClient,
+    /// Maximum number of attempts per webhook.
+    attempts: u16,
+    /// Delay before the first retry, which doubles after every attempt.
+    backoff: Duration,
+}
+
+impl Webhooks {
+    pub const DEFAULT_ATTEMPTS: u16 = 5;
+    pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+    pub fn new(http: HttpsClient, attempts: u16, backoff: Duration) -> Self {
+        Self { http, attempts: attempts.max(1), backoff }
+    }
+
+    /// Sends `payload` to every webhook of `guild` at once. Payloads that cannot be delivered are kept as dead
+    /// letters without their signing timestamp, so redelivering one takes a fresh signature. Once `shutdown` is set,
+    /// each webhook gets one last attempt without waiting for the backoff.
+    pub async fn deliver<S: QuizStore>(
+        &self,
+        db: &S,
+        metrics: &Metrics,
+        guild: NonZeroU64,
+        payload: &str,
+        shutdown: &watch::Receiver<bool>,
+    ) {
+        let hooks = match db.get_webhooks(guild).await {
+            Ok(hooks) => hooks,
+            Err(err) => {
+                tracing::error!(error = ?err, "cannot look up webhooks");
+                return;
+            }
+        };
+
+        let deliveries = hooks.iter().map(|hook| async move {
+            let (attempts, error) = match self.send_with_retries(hook, payload, shutdown.clone()).await {
+                Ok(()) => return,
+                Err(failure) => failure,
+            };
+            tracing::error!(url = hook.url, attempts, error, "webhook delivery failed");
+            metrics.record_webhook_failure();
+            let letter = DeadLetter { url: hook.url.clone(), payload: payload.into(), attempts, error };
+            if let Err(err) = db.add_dead_letter(guild, &letter).await {
+                tracing::error!(error = ?err, url = hook.url, "cannot keep dead letter, payload is lost");
+            }
+        });
+        futures_util::future::join_all(deliveries).await;
+    }
+
+    /// Returns the number of attempts and the last error if every attempt failed.
+    async fn send_with_retries(
+        &self,
+        hook: &Webhook,
+        payload: &str,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<(), (u16, String)> {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+        loop {
+            let Failure { transient, message } = match self.send(hook, payload).await {
+                Ok(()) => return Ok(()),
+                Err(failure) => failure,
+            };
+            if attempt >= self.attempts || !transient || *shutdown.borrow() {
+                return Err((attempt, message));
+            }
+            tracing::warn!(url = hook.url, attempt, ?backoff, error = message, "webhook delivery failed, retrying");
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => (),
+                _ = shutdown.wait_for(|&stop| stop) => (),
+            }
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+
+    async fn send(&self, hook: &Webhook, payload: &str) -> Result<(), Failure> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+        let signature = sign_webhook(&hook.secret, timestamp, payload.as_bytes());
+        let request = Request::post(hook.url.as_str())
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, concat!("quizzo/", env!("CARGO_PKG_VERSION")))
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(Full::new(Bytes::copy_from_slice(payload.as_bytes())))
+            .map_err(|err| Failure { transient: false, message: format!("invalid request: {err}") })?;
+
+        let exchange = async {
+            let response = self
+                .http
+                .request(request)
+                .await
+                .map_err(|err| Failure { transient: true, message: err.to_string() })?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            // Only the start of the body is kept for diagnosis
+            let body = response.into_body().collect().await.map(|body| body.to_bytes()).unwrap_or_default();
+            let body = String::from_utf8_lossy(&body[..body.len().min(256)]).into_owned();
+            let transient = status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429;
+            Err(Failure { transient, message: format!("status {status}: {body}") })
+        };
+        match tokio::time::timeout(REQUEST_TIMEOUT, exchange).await {
+            Ok(result) => result,
+            Err(_) => Err(Failure { transient: true, message: format!("timed out after {REQUEST_TIMEOUT:?}") }),
+        }
+    }
+}
+
+impl<S: QuizStore> App<S> {
+    /// Sends the results of quizzes finished in `guild` to `url`. Returns the secret that signs the payloads.
+    pub async fn add_webhook(&self, guild: NonZeroU64, url: &str) -> db::error::Result<String> {
+        let secret = webhook_secret().map_err(|_| db::error::Error::Fatal)?;
+        self.bot.store().set_webhook(guild, url, &secret).await?;
+        Ok(secret)
+    }
+
+    /// Lists the payloads that could not be delivered to the webhooks of `guild`, oldest first.
+    pub async fn dead_letters(&self, guild: NonZeroU64) -> db::error::Result<Vec<DeadLetter>> {
+        self.bot.store().get_dead_letters(guild).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sign_webhook;
+
+    #[test]
+    fn signature_matches_reference() {
+        // Computed independently with `printf '1700000000.{}' | openssl dgst -sha256 -hmac secret`
+        let signature = sign_webhook("secret", 1_700_000_000, b"{}");
+        assert_eq!(signature, "sha256=b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163");
+    }
+}
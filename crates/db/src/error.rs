@@ -1,11 +1,89 @@
+use core::{
+    fmt::{self, Display},
+    ops::RangeInclusive,
+};
+
+/// A data constraint of the schema that some input violated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    /// Questions must not be empty nor exceed [`Constraint::MAX_TEXT_LEN`] characters.
+    QuestionLength,
+    /// Choices must not exceed [`Constraint::MAX_TEXT_LEN`] characters.
+    ChoiceLength,
+    /// Quizzes hold at most [`Constraint::MAX_CHOICES`] choices.
+    ChoiceCount,
+    /// The answer must point to one of the `choices` of the quiz.
+    AnswerRange { choices: u16 },
+    /// The removed choice must be one of the `choices` of the quiz.
+    ChoiceIndex { choices: u16 },
+    /// Quizzes must expire within [`Constraint::EXPIRATION`] seconds.
+    Expiration,
+    /// Webhook URLs must be HTTP(S) and not exceed [`Constraint::MAX_URL_LEN`] characters.
+    WebhookUrl,
+    /// Webhook secrets must not be empty nor exceed [`Constraint::MAX_SECRET_LEN`] characters.
+    WebhookSecret,
+    /// Dead letters must record at least one delivery attempt.
+    DeadLetterAttempts,
+    /// Embed colors must be 24-bit RGB, i.e., at most [`Constraint::MAX_COLOR`].
+    Color,
+    /// Guilds may restrict quizzes to at most [`Constraint::MAX_CHANNELS`] channels.
+    ChannelCount,
+    /// Guilds may allow at most [`Constraint::MAX_ANSWER_CHANGES`] answer changes.
+    AnswerChanges,
+}
+
+impl Constraint {
+    /// Maximum number of characters in a `VARCHAR(100)` column.
+    pub const MAX_TEXT_LEN: usize = 100;
+    /// Maximum number of choices allowed by `quiz_choices_length_check`.
+    pub const MAX_CHOICES: usize = 25;
+    /// Allowed range for `quiz_expiration_check`.
+    pub const EXPIRATION: RangeInclusive<u16> = 10..=600;
+    /// Maximum number of characters in the `webhook.url` column.
+    pub const MAX_URL_LEN: usize = 2048;
+    /// Maximum number of characters in the `webhook.secret` column.
+    pub const MAX_SECRET_LEN: usize = 128;
+    /// Largest value allowed by `guild_settings_color_check`.
+    pub const MAX_COLOR: u32 = 0xFF_FFFF;
+    /// Maximum number of channels allowed by `guild_settings_channels_length_check`.
+    pub const MAX_CHANNELS: usize = 25;
+    /// Largest value allowed by `guild_settings_answer_changes_check`.
+    pub const MAX_ANSWER_CHANGES: u16 = 10;
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = Self::MAX_TEXT_LEN;
+        match *self {
+            Self::QuestionLength => write!(f, "question must have between 1 and {text} characters"),
+            Self::ChoiceLength => write!(f, "choice must have at most {text} characters"),
+            Self::ChoiceCount => write!(f, "quiz already has the maximum of {} choices", Self::MAX_CHOICES),
+            Self::AnswerRange { choices: 0 } => f.write_str("quiz has no choices to pick an answer from"),
+            Self::AnswerRange { choices } => write!(f, "answer must be between 0 and {}", choices - 1),
+            Self::ChoiceIndex { choices: 0 } => f.write_str("quiz has no choices to remove"),
+            Self::ChoiceIndex { choices } => write!(f, "index must be between 0 and {}", choices - 1),
+            Self::Expiration => {
+                let (min, max) = (Self::EXPIRATION.start(), Self::EXPIRATION.end());
+                write!(f, "expiration must be between {min} and {max} seconds")
+            }
+            Self::WebhookUrl => write!(f, "URL must be HTTP(S) with at most {} characters", Self::MAX_URL_LEN),
+            Self::WebhookSecret => write!(f, "secret must have between 1 and {} characters", Self::MAX_SECRET_LEN),
+            Self::DeadLetterAttempts => f.write_str("dead letter must record at least one attempt"),
+            Self::Color => write!(f, "color must be at most {:#08X}", Self::MAX_COLOR),
+            Self::ChannelCount => write!(f, "quizzes may be restricted to at most {} channels", Self::MAX_CHANNELS),
+            Self::AnswerChanges => write!(f, "answers may be changed at most {} times", Self::MAX_ANSWER_CHANGES),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     /// Quiz does not exist.
     NotFound,
-    /// Input cannot be accepted due to data constraints.
-    BadInput,
-    /// Attempted to insert too many choices.
-    TooMany,
+    /// Input cannot be accepted due to the given data constraint.
+    BadInput(Constraint),
+    /// The author (or the guild if `guild` is set) already stores `limit` quizzes.
+    QuotaExceeded { limit: u16, guild: bool },
     /// An unexpected and unrecoverable error.
     Fatal,
 }
@@ -1,22 +1,274 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "memory", feature = "rustls")), no_std)]
 
 extern crate alloc;
 
 pub mod error;
+#[cfg(any(test, feature = "memory"))]
+pub mod memory;
+#[cfg(feature = "rustls")]
+pub mod tls;
 
-use alloc::boxed::Box;
-use core::num::{NonZeroI16, NonZeroU64};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    future::Future,
+    num::{NonZeroI16, NonZeroU16, NonZeroU64},
+};
+use error::Constraint;
 use tokio_postgres::error::SqlState;
 
 pub use futures_util::{TryStream, TryStreamExt};
 pub use model::{Quiz, RawQuiz};
 pub use tokio_postgres::{tls::NoTls, Client, Config};
 
-pub struct Database(Client);
+/// Fields to replace in a quiz, where `None` keeps the current value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuizPatch {
+    pub question: Option<String>,
+    /// Seconds the quiz runs once started.
+    pub expiration: Option<u16>,
+    /// Zero-indexed correct answer.
+    pub answer: Option<u16>,
+}
+
+/// An endpoint that receives the results of every quiz finished in its guild.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub url: String,
+    /// Shared secret for signing each payload.
+    pub secret: String,
+}
+
+/// A webhook payload that could not be delivered after every attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetter {
+    pub url: String,
+    /// JSON payload that was sent. Its signature is not kept since it covers the timestamp of each attempt.
+    pub payload: String,
+    pub attempts: u16,
+    /// Why the last attempt failed.
+    pub error: String,
+}
+
+/// How the bot behaves in one guild.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildSettings {
+    /// Expiration (in seconds) of quizzes created in the guild. `None` defers to the configured default.
+    pub expiration: Option<u16>,
+    /// Embed color as `0xRRGGBB`. `None` defers to the configured brand color.
+    pub color: Option<u32>,
+    /// Whether the results mention (and thus ping) the winners.
+    pub ping_winners: bool,
+    /// How often participants may change their answer before the quiz expires.
+    pub answer_policy: AnswerPolicy,
+    /// Channels where quizzes may be started. Empty allows every channel.
+    pub channels: Vec<NonZeroU64>,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            expiration: None,
+            color: None,
+            ping_winners: true,
+            answer_policy: AnswerPolicy::default(),
+            channels: Vec::new(),
+        }
+    }
+}
+
+/// Decides whether a participant may replace their answer to a running quiz.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnswerPolicy {
+    /// Every answer replaces the previous one.
+    #[default]
+    LastWins,
+    /// The first answer is final.
+    FirstLocks,
+    /// The answer may be changed at most this many times.
+    Limited(NonZeroU16),
+}
+
+impl AnswerPolicy {
+    /// Builds the policy from the number of allowed changes, where `None` allows any number.
+    pub fn from_changes(changes: Option<u16>) -> Self {
+        match changes.map(NonZeroU16::new) {
+            None => Self::LastWins,
+            Some(None) => Self::FirstLocks,
+            Some(Some(limit)) => Self::Limited(limit),
+        }
+    }
+
+    /// Number of allowed changes, where `None` allows any number.
+    pub fn changes(self) -> Option<u16> {
+        match self {
+            Self::LastWins => None,
+            Self::FirstLocks => Some(0),
+            Self::Limited(limit) => Some(limit.get()),
+        }
+    }
+
+    /// Whether an answer that was already changed `changes` times may be changed again.
+    pub fn allows_change(self, changes: u16) -> bool {
+        self.changes().is_none_or(|limit| changes < limit)
+    }
+}
+
+/// Maximum number of quizzes that may be stored at once. `None` lifts the limit, which is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quota {
+    /// Applies to each author.
+    pub per_user: Option<u16>,
+    /// Applies to the quizzes created in each guild, regardless of their authors.
+    pub per_guild: Option<u16>,
+}
+
+/// Persistent storage for quizzes that have not yet been started.
+///
+/// Every implementation must uphold the constraints of `scripts/init.sql` and report violations
+/// with the same [`error::Error`] variants as the PostgreSQL-backed [`Database`].
+pub trait QuizStore: Send + Sync + 'static {
+    /// Checks that the store is reachable with the cheapest possible query.
+    fn ping(&self) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Creates a new quiz that runs for `expiration` seconds once started. Returns the generated quiz ID.
+    /// Quizzes created in a `guild` also count towards its [`Quota`].
+    fn init_quiz(
+        &self,
+        user: NonZeroU64,
+        guild: Option<NonZeroU64>,
+        question: &str,
+        expiration: u16,
+    ) -> impl Future<Output = error::Result<NonZeroI16>> + Send;
+
+    /// Retrieves a quiz owned by `user`.
+    fn get_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> impl Future<Output = error::Result<RawQuiz>> + Send;
+
+    /// Streams all the quizzes owned by `user`.
+    fn get_quizzes_by_user(
+        &self,
+        user: NonZeroU64,
+    ) -> impl Future<Output = error::Result<impl TryStream<Ok = Quiz, Error = error::Error> + Send + '_>> + Send;
+
+    /// Removes a quiz that has an answer set. Returns the removed quiz.
+    fn pop_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> impl Future<Output = error::Result<RawQuiz>> + Send;
+
+    /// Removes a quiz regardless of whether its answer is set. Returns the removed quiz.
+    fn remove_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> impl Future<Output = error::Result<RawQuiz>> + Send;
+
+    /// Appends a new choice to the quiz.
+    fn add_choice(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        choice: &str,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Removes the choice at `index` and resets the answer. Returns the removed choice.
+    fn remove_choice(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        index: u32,
+    ) -> impl Future<Output = error::Result<Box<str>>> + Send;
+
+    /// Replaces the question of the quiz.
+    fn set_question(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        question: &str,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Sets the zero-indexed correct answer of the quiz.
+    fn set_answer(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        answer: u16,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Sets how long (in seconds) the quiz runs once started.
+    fn set_expiration(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        expiration: u16,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Applies every field of `patch` at once, so that a rejected field leaves the quiz untouched.
+    fn update_quiz(
+        &self,
+        user: NonZeroU64,
+        quiz: NonZeroI16,
+        patch: &QuizPatch,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Replaces the API key of `user` with the one whose SHA-256 hash is `digest`.
+    fn set_api_key(&self, user: NonZeroU64, digest: [u8; 32]) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Revokes the API key of `user`.
+    fn remove_api_key(&self, user: NonZeroU64) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Resolves the owner of the API key whose SHA-256 hash is `digest`.
+    fn get_user_by_api_key(&self, digest: [u8; 32]) -> impl Future<Output = error::Result<NonZeroU64>> + Send;
+
+    /// Reports finished quizzes in `guild` to `url`, replacing the secret if the webhook already exists.
+    fn set_webhook(&self, guild: NonZeroU64, url: &str, secret: &str)
+        -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Stops reporting finished quizzes in `guild` to `url`.
+    fn remove_webhook(&self, guild: NonZeroU64, url: &str) -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Lists every webhook of `guild`.
+    fn get_webhooks(&self, guild: NonZeroU64) -> impl Future<Output = error::Result<Vec<Webhook>>> + Send;
+
+    /// Keeps a payload for `guild` that could not be delivered.
+    fn add_dead_letter(&self, guild: NonZeroU64, letter: &DeadLetter)
+        -> impl Future<Output = error::Result<()>> + Send;
+
+    /// Lists every undelivered payload of `guild`, oldest first.
+    fn get_dead_letters(&self, guild: NonZeroU64) -> impl Future<Output = error::Result<Vec<DeadLetter>>> + Send;
+
+    /// Looks up the settings of `guild`. Guilds that never changed them get [`GuildSettings::default`].
+    fn get_guild_settings(&self, guild: NonZeroU64) -> impl Future<Output = error::Result<GuildSettings>> + Send;
+
+    /// Replaces the settings of `guild`.
+    fn set_guild_settings(
+        &self,
+        guild: NonZeroU64,
+        settings: &GuildSettings,
+    ) -> impl Future<Output = error::Result<()>> + Send;
+}
+
+/// PostgreSQL-backed [`QuizStore`].
+pub struct Database {
+    client: Client,
+    quota: Quota,
+}
 
 impl From<Client> for Database {
     fn from(client: Client) -> Self {
-        Self(client)
+        Self { client, quota: Quota::default() }
+    }
+}
+
+impl Database {
+    /// Limits how many quizzes may be stored at once. Defaults to [`Quota::default`].
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Counts the choices of a quiz in order to report which indices would have been in range.
+    async fn count_choices(&self, uid: i64, qid: i16) -> error::Result<u16> {
+        let row = self
+            .client
+            .query_opt("SELECT CARDINALITY(choices) AS count FROM quiz WHERE author = $1 AND id = $2", &[&uid, &qid])
+            .await
+            .map_err(|_| error::Error::Fatal)?
+            .ok_or(error::Error::NotFound)?;
+        let count: i32 = row.try_get("count").map_err(|_| error::Error::Fatal)?;
+        u16::try_from(count).map_err(|_| error::Error::Fatal)
     }
 }
 
@@ -35,40 +287,71 @@ fn deserialize_quiz_from_row(row: tokio_postgres::Row) -> error::Result<Quiz> {
     Ok(Quiz { id, raw })
 }
 
-impl Database {
-    pub async fn init_quiz(&self, user: NonZeroU64, question: &str) -> error::Result<NonZeroI16> {
+impl QuizStore for Database {
+    async fn ping(&self) -> error::Result<()> {
+        self.client.simple_query("SELECT 1").await.map(drop).map_err(|_| error::Error::Fatal)
+    }
+
+    async fn init_quiz(
+        &self,
+        user: NonZeroU64,
+        guild: Option<NonZeroU64>,
+        question: &str,
+        expiration: u16,
+    ) -> error::Result<NonZeroI16> {
+        let expiration = i16::try_from(expiration).map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
         let uid = user.get() as i64;
+        let gid = guild.map(|guild| guild.get() as i64);
+        let per_user = self.quota.per_user.map(i64::from);
+        let per_guild = self.quota.per_guild.map(i64::from);
+
         let err = match self
-            .0
-            .query_opt("INSERT INTO quiz (author, question) VALUES ($1, $2) RETURNING id", &[&uid, &question])
+            .client
+            .query_one(
+                "SELECT id, by_user, by_guild FROM init_quiz($1, $2, $3, $4, $5, $6)",
+                &[&uid, &gid, &question, &expiration, &per_user, &per_guild],
+            )
             .await
         {
             Ok(row) => {
-                let row = row.ok_or(error::Error::Fatal)?;
-                let id: i16 = row.try_get("id").map_err(|_| error::Error::Fatal)?;
-                return NonZeroI16::new(id).ok_or(error::Error::Fatal);
+                let id: Option<i16> = row.try_get("id").map_err(|_| error::Error::Fatal)?;
+                if let Some(id) = id {
+                    return NonZeroI16::new(id).ok_or(error::Error::Fatal);
+                }
+                let by_user: i64 = row.try_get("by_user").map_err(|_| error::Error::Fatal)?;
+                return Err(match (self.quota.per_user, self.quota.per_guild) {
+                    (Some(limit), _) if by_user >= i64::from(limit) => {
+                        error::Error::QuotaExceeded { limit, guild: false }
+                    }
+                    (_, Some(limit)) => error::Error::QuotaExceeded { limit, guild: true },
+                    _ => error::Error::Fatal,
+                });
             }
             Err(err) => err,
         };
 
         let err = err.as_db_error().ok_or(error::Error::Fatal)?;
-        if *err.code() != SqlState::CHECK_VIOLATION {
-            return Err(error::Error::Fatal);
-        }
-
-        let constraint = err.constraint().ok_or(error::Error::Fatal)?;
-        if constraint == "quiz_question_check" {
-            return Err(error::Error::BadInput);
-        }
-
-        Err(error::Error::Fatal)
+        Err(match (err.code(), err.constraint()) {
+            // We tried to insert an empty question.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_question_check")) => {
+                error::Error::BadInput(Constraint::QuestionLength)
+            }
+            // We tried to insert an expiration outside the allowed range.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_expiration_check")) => {
+                error::Error::BadInput(Constraint::Expiration)
+            }
+            // We tried to insert a question that is too long for the `VARCHAR`.
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) => error::Error::BadInput(Constraint::QuestionLength),
+            // Unexpected error type.
+            _ => error::Error::Fatal,
+        })
     }
 
-    pub async fn get_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+    async fn get_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
         let uid = user.get() as i64;
         let qid = quiz.get();
         let row = self
-            .0
+            .client
             .query_opt(
                 "SELECT question, choices, answer, expiration FROM quiz WHERE author = $1 AND id = $2",
                 &[&uid, &qid],
@@ -79,13 +362,13 @@ impl Database {
         deserialize_raw_quiz_from_row(row).map_err(|_| error::Error::Fatal)
     }
 
-    pub async fn get_quizzes_by_user(
+    async fn get_quizzes_by_user(
         &self,
         user: NonZeroU64,
     ) -> error::Result<impl TryStream<Ok = Quiz, Error = error::Error> + '_> {
         let uid = user.get() as i64;
         Ok(self
-            .0
+            .client
             .query_raw("SELECT id, question, choices, answer, expiration FROM quiz WHERE author = $1", &[&uid])
             .await
             .map_err(|_| error::Error::Fatal)?
@@ -93,11 +376,11 @@ impl Database {
             .and_then(|row| core::future::ready(deserialize_quiz_from_row(row))))
     }
 
-    pub async fn pop_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+    async fn pop_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
         let uid = user.get() as i64;
         let qid = quiz.get();
         let row = self
-            .0
+            .client
             .query_opt(
                 "DELETE FROM quiz WHERE author = $1 AND id = $2 AND answer IS NOT NULL RETURNING question, choices, answer, expiration",
                 &[&uid, &qid],
@@ -108,11 +391,26 @@ impl Database {
         deserialize_raw_quiz_from_row(row).map_err(|_| error::Error::Fatal)
     }
 
-    pub async fn add_choice(&self, user: NonZeroU64, quiz: NonZeroI16, choice: &str) -> error::Result<()> {
+    async fn remove_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        let uid = user.get() as i64;
+        let qid = quiz.get();
+        let row = self
+            .client
+            .query_opt(
+                "DELETE FROM quiz WHERE author = $1 AND id = $2 RETURNING question, choices, answer, expiration",
+                &[&uid, &qid],
+            )
+            .await
+            .map_err(|_| error::Error::Fatal)?
+            .ok_or(error::Error::NotFound)?;
+        deserialize_raw_quiz_from_row(row).map_err(|_| error::Error::Fatal)
+    }
+
+    async fn add_choice(&self, user: NonZeroU64, quiz: NonZeroI16, choice: &str) -> error::Result<()> {
         let uid = user.get() as i64;
         let qid = quiz.get();
         let err = match self
-            .0
+            .client
             .execute(
                 "UPDATE quiz SET choices = array_append(choices, $3) WHERE author = $1 AND id = $2",
                 &[&uid, &qid, &choice],
@@ -126,43 +424,54 @@ impl Database {
         };
 
         let err = err.as_db_error().ok_or(error::Error::Fatal)?;
-        let constraint = err.constraint().ok_or(error::Error::Fatal)?;
-        Err(match (err.code(), constraint) {
+        Err(match (err.code(), err.constraint()) {
             // We tried to append too many values to the array.
-            (&SqlState::CHECK_VIOLATION, "quiz_choices_length_check") => error::Error::TooMany,
+            (&SqlState::CHECK_VIOLATION, Some("quiz_choices_length_check")) => {
+                error::Error::BadInput(Constraint::ChoiceCount)
+            }
             // We tried to append a string that is too long for the `VARCHAR`.
-            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, "quiz_choices_check") => error::Error::BadInput,
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) => error::Error::BadInput(Constraint::ChoiceLength),
             // Unexpected error type.
             _ => error::Error::Fatal,
         })
     }
 
-    pub async fn remove_choice(&self, user: NonZeroU64, quiz: NonZeroI16, index: u32) -> error::Result<Box<str>> {
-        let index = i32::try_from(index).map_err(|_| error::Error::BadInput)?;
+    async fn remove_choice(&self, user: NonZeroU64, quiz: NonZeroI16, index: u32) -> error::Result<Box<str>> {
         let uid = user.get() as i64;
         let qid = quiz.get();
+        let Ok(index) = i32::try_from(index) else {
+            let choices = self.count_choices(uid, qid).await?;
+            return Err(error::Error::BadInput(Constraint::ChoiceIndex { choices }));
+        };
         let row = self
-            .0
+            .client
             .query_opt(
                 "WITH old AS (SELECT * FROM quiz WHERE author = $1 AND id = $2) \
                  UPDATE quiz SET answer = DEFAULT, choices = quiz.choices[1:$3] || quiz.choices[$3+2:] \
                  FROM old \
                  WHERE quiz.author = old.author AND quiz.id = old.id \
-                 RETURNING old.choices[$3+1] AS choice",
+                 RETURNING old.choices[$3+1] AS choice, CARDINALITY(old.choices) AS count",
                 &[&uid, &qid, &index],
             )
             .await
             .map_err(|_| error::Error::Fatal)?
             .ok_or(error::Error::NotFound)?;
-        let choice: Box<str> = row.try_get("choice").map_err(|_| error::Error::Fatal)?;
-        Ok(choice)
+
+        // Out of bounds indices yield a `NULL` choice.
+        let choice: Option<Box<str>> = row.try_get("choice").map_err(|_| error::Error::Fatal)?;
+        if let Some(choice) = choice {
+            return Ok(choice);
+        }
+        let count: i32 = row.try_get("count").map_err(|_| error::Error::Fatal)?;
+        let choices = u16::try_from(count).map_err(|_| error::Error::Fatal)?;
+        Err(error::Error::BadInput(Constraint::ChoiceIndex { choices }))
     }
 
-    pub async fn set_question(&self, user: NonZeroU64, quiz: NonZeroI16, question: &str) -> error::Result<()> {
+    async fn set_question(&self, user: NonZeroU64, quiz: NonZeroI16, question: &str) -> error::Result<()> {
         let uid = user.get() as i64;
         let qid = quiz.get();
         let err = match self
-            .0
+            .client
             .execute("UPDATE quiz SET question = $3 WHERE author = $1 AND id = $2", &[&uid, &qid, &question])
             .await
         {
@@ -173,103 +482,377 @@ impl Database {
         };
 
         let err = err.as_db_error().ok_or(error::Error::Fatal)?;
-        if *err.code() != SqlState::CHECK_VIOLATION {
-            return Err(error::Error::Fatal);
-        }
-
-        let constraint = err.constraint().ok_or(error::Error::Fatal)?;
-        if constraint != "question_check" {
-            return Err(error::Error::Fatal);
-        }
-
-        Err(error::Error::BadInput)
+        Err(match (err.code(), err.constraint()) {
+            // We tried to set an empty question.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_question_check")) => {
+                error::Error::BadInput(Constraint::QuestionLength)
+            }
+            // We tried to set a string that is too long for the `VARCHAR`.
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) => error::Error::BadInput(Constraint::QuestionLength),
+            // Unexpected error type.
+            _ => error::Error::Fatal,
+        })
     }
 
-    pub async fn set_answer(&self, user: NonZeroU64, quiz: NonZeroI16, answer: u16) -> error::Result<()> {
-        let answer = i16::try_from(answer).map_err(|_| error::Error::BadInput)?;
+    async fn set_answer(&self, user: NonZeroU64, quiz: NonZeroI16, answer: u16) -> error::Result<()> {
         let uid = user.get() as i64;
         let qid = quiz.get();
+        let Ok(answer) = i16::try_from(answer) else {
+            let choices = self.count_choices(uid, qid).await?;
+            return Err(error::Error::BadInput(Constraint::AnswerRange { choices }));
+        };
         let err = match self
-            .0
-            .execute("UPDATE quiz SET answer = $3 WHERE author = $1 AND id = $2", &[&uid, &qid, &answer])
+            .client
+            .execute(
+                "UPDATE quiz SET answer = $3 WHERE author = $1 AND id = $2 AND CARDINALITY(choices) > 0",
+                &[&uid, &qid, &answer],
+            )
             .await
         {
             Ok(1) => return Ok(()),
-            Ok(0) => return Err(error::Error::NotFound),
+            // `quiz_check` passes for empty choices since their `ARRAY_LENGTH` is `NULL`, hence the extra condition.
+            // Counting the choices tells whether the quiz is missing or merely empty.
+            Ok(0) => {
+                let choices = self.count_choices(uid, qid).await?;
+                return Err(error::Error::BadInput(Constraint::AnswerRange { choices }));
+            }
             Err(err) => err,
             _ => return Err(error::Error::Fatal),
         };
 
         let err = err.as_db_error().ok_or(error::Error::Fatal)?;
-        if *err.code() != SqlState::CHECK_VIOLATION {
-            return Err(error::Error::Fatal);
+        match (err.code(), err.constraint()) {
+            // We tried to point the answer past the last choice. PostgreSQL names this multi-column check `quiz_check`.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_check")) => {
+                let choices = self.count_choices(uid, qid).await?;
+                Err(error::Error::BadInput(Constraint::AnswerRange { choices }))
+            }
+            // Unexpected error type.
+            _ => Err(error::Error::Fatal),
         }
+    }
 
-        let constraint = err.constraint().ok_or(error::Error::Fatal)?;
-        if constraint != "answer_check" {
-            return Err(error::Error::Fatal);
-        }
+    async fn set_expiration(&self, user: NonZeroU64, quiz: NonZeroI16, expiration: u16) -> error::Result<()> {
+        let expiration = i16::try_from(expiration).map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let uid = user.get() as i64;
+        let qid = quiz.get();
+        let err = match self
+            .client
+            .execute("UPDATE quiz SET expiration = $3 WHERE author = $1 AND id = $2", &[&uid, &qid, &expiration])
+            .await
+        {
+            Ok(1) => return Ok(()),
+            Ok(0) => return Err(error::Error::NotFound),
+            Err(err) => err,
+            _ => return Err(error::Error::Fatal),
+        };
 
-        Err(error::Error::BadInput)
+        let err = err.as_db_error().ok_or(error::Error::Fatal)?;
+        Err(match (err.code(), err.constraint()) {
+            // We tried to set an expiration outside the allowed range.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_expiration_check")) => {
+                error::Error::BadInput(Constraint::Expiration)
+            }
+            // Unexpected error type.
+            _ => error::Error::Fatal,
+        })
     }
 
-    pub async fn set_expiration(&self, user: NonZeroU64, quiz: NonZeroI16, expiration: u16) -> error::Result<()> {
-        let expiration = i16::try_from(expiration).map_err(|_| error::Error::BadInput)?;
+    async fn update_quiz(&self, user: NonZeroU64, quiz: NonZeroI16, patch: &QuizPatch) -> error::Result<()> {
         let uid = user.get() as i64;
         let qid = quiz.get();
+        let expiration = patch
+            .expiration
+            .map(i16::try_from)
+            .transpose()
+            .map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let Ok(answer) = patch.answer.map(i16::try_from).transpose() else {
+            let choices = self.count_choices(uid, qid).await?;
+            return Err(error::Error::BadInput(Constraint::AnswerRange { choices }));
+        };
+        let question = patch.question.as_deref();
         let err = match self
-            .0
-            .execute("UPDATE quiz SET expiration = $3 WHERE author = $1 AND id = $2", &[&uid, &qid, &expiration])
+            .client
+            .execute(
+                "UPDATE quiz SET question = COALESCE($3, question), expiration = COALESCE($4, expiration), \
+                    answer = COALESCE($5, answer) \
+                 WHERE author = $1 AND id = $2 AND ($5::SMALLINT IS NULL OR CARDINALITY(choices) > 0)",
+                &[&uid, &qid, &question, &expiration, &answer],
+            )
             .await
         {
             Ok(1) => return Ok(()),
+            // Just like in `set_answer`, quizzes without choices are skipped when an answer is given.
+            Ok(0) if answer.is_some() => {
+                let choices = self.count_choices(uid, qid).await?;
+                return Err(error::Error::BadInput(Constraint::AnswerRange { choices }));
+            }
             Ok(0) => return Err(error::Error::NotFound),
             Err(err) => err,
             _ => return Err(error::Error::Fatal),
         };
 
         let err = err.as_db_error().ok_or(error::Error::Fatal)?;
-        if *err.code() != SqlState::CHECK_VIOLATION {
-            return Err(error::Error::Fatal);
+        match (err.code(), err.constraint()) {
+            // We tried to point the answer past the last choice.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_check")) => {
+                let choices = self.count_choices(uid, qid).await?;
+                Err(error::Error::BadInput(Constraint::AnswerRange { choices }))
+            }
+            // We tried to set an expiration outside the allowed range.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_expiration_check")) => {
+                Err(error::Error::BadInput(Constraint::Expiration))
+            }
+            // We tried to set an empty question.
+            (&SqlState::CHECK_VIOLATION, Some("quiz_question_check")) => {
+                Err(error::Error::BadInput(Constraint::QuestionLength))
+            }
+            // We tried to set a question that is too long for the `VARCHAR`.
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) => Err(error::Error::BadInput(Constraint::QuestionLength)),
+            // Unexpected error type.
+            _ => Err(error::Error::Fatal),
+        }
+    }
+
+    async fn set_api_key(&self, user: NonZeroU64, digest: [u8; 32]) -> error::Result<()> {
+        let uid = user.get() as i64;
+        let digest = digest.as_slice();
+        self.client
+            .execute(
+                "INSERT INTO api_key (author, digest) VALUES ($1, $2) \
+                 ON CONFLICT (author) DO UPDATE SET digest = EXCLUDED.digest",
+                &[&uid, &digest],
+            )
+            .await
+            .map_err(|_| error::Error::Fatal)?;
+        Ok(())
+    }
+
+    async fn remove_api_key(&self, user: NonZeroU64) -> error::Result<()> {
+        let uid = user.get() as i64;
+        match self.client.execute("DELETE FROM api_key WHERE author = $1", &[&uid]).await {
+            Ok(1) => Ok(()),
+            Ok(0) => Err(error::Error::NotFound),
+            _ => Err(error::Error::Fatal),
         }
+    }
+
+    async fn get_user_by_api_key(&self, digest: [u8; 32]) -> error::Result<NonZeroU64> {
+        let digest = digest.as_slice();
+        let row = self
+            .client
+            .query_opt("SELECT author FROM api_key WHERE digest = $1", &[&digest])
+            .await
+            .map_err(|_| error::Error::Fatal)?
+            .ok_or(error::Error::NotFound)?;
+        let uid: i64 = row.try_get("author").map_err(|_| error::Error::Fatal)?;
+        NonZeroU64::new(uid as u64).ok_or(error::Error::Fatal)
+    }
+
+    async fn set_webhook(&self, guild: NonZeroU64, url: &str, secret: &str) -> error::Result<()> {
+        let gid = guild.get() as i64;
+        let err = match self
+            .client
+            .execute(
+                "INSERT INTO webhook (guild, url, secret) VALUES ($1, $2, $3) \
+                 ON CONFLICT (guild, url) DO UPDATE SET secret = EXCLUDED.secret",
+                &[&gid, &url, &secret],
+            )
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
 
-        let constraint = err.constraint().ok_or(error::Error::Fatal)?;
-        if constraint != "expiration_check" {
-            return Err(error::Error::Fatal);
+        let err = err.as_db_error().ok_or(error::Error::Fatal)?;
+        Err(match (err.code(), err.constraint()) {
+            // We tried to insert a URL that is not HTTP(S).
+            (&SqlState::CHECK_VIOLATION, Some("webhook_url_check")) => error::Error::BadInput(Constraint::WebhookUrl),
+            // We tried to insert an empty secret.
+            (&SqlState::CHECK_VIOLATION, Some("webhook_secret_check")) => {
+                error::Error::BadInput(Constraint::WebhookSecret)
+            }
+            // We tried to insert a string that is too long for the `VARCHAR`, which does not say which one it was.
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) if url.chars().count() > Constraint::MAX_URL_LEN => {
+                error::Error::BadInput(Constraint::WebhookUrl)
+            }
+            (&SqlState::STRING_DATA_RIGHT_TRUNCATION, _) => error::Error::BadInput(Constraint::WebhookSecret),
+            // Unexpected error type.
+            _ => error::Error::Fatal,
+        })
+    }
+
+    async fn remove_webhook(&self, guild: NonZeroU64, url: &str) -> error::Result<()> {
+        let gid = guild.get() as i64;
+        match self.client.execute("DELETE FROM webhook WHERE guild = $1 AND url = $2", &[&gid, &url]).await {
+            Ok(1) => Ok(()),
+            Ok(0) => Err(error::Error::NotFound),
+            _ => Err(error::Error::Fatal),
         }
+    }
+
+    async fn get_webhooks(&self, guild: NonZeroU64) -> error::Result<Vec<Webhook>> {
+        let gid = guild.get() as i64;
+        let rows = self
+            .client
+            .query("SELECT url, secret FROM webhook WHERE guild = $1 ORDER BY url", &[&gid])
+            .await
+            .map_err(|_| error::Error::Fatal)?;
+        rows.into_iter()
+            .map(|row| {
+                let url = row.try_get("url")?;
+                let secret = row.try_get("secret")?;
+                Ok(Webhook { url, secret })
+            })
+            .collect::<Result<_, tokio_postgres::Error>>()
+            .map_err(|_| error::Error::Fatal)
+    }
+
+    async fn add_dead_letter(&self, guild: NonZeroU64, letter: &DeadLetter) -> error::Result<()> {
+        let gid = guild.get() as i64;
+        let attempts =
+            i16::try_from(letter.attempts).map_err(|_| error::Error::BadInput(Constraint::DeadLetterAttempts))?;
+        let DeadLetter { url, payload, error, .. } = letter;
+        self.client
+            .execute(
+                "INSERT INTO webhook_dead_letter (guild, url, payload, attempts, error) \
+                 VALUES ($1, $2, $3::TEXT::JSON, $4, $5)",
+                &[&gid, url, payload, &attempts, error],
+            )
+            .await
+            .map_err(|_| error::Error::Fatal)?;
+        Ok(())
+    }
+
+    async fn get_dead_letters(&self, guild: NonZeroU64) -> error::Result<Vec<DeadLetter>> {
+        let gid = guild.get() as i64;
+        let rows = self
+            .client
+            .query(
+                "SELECT url, payload::TEXT AS payload, attempts, error FROM webhook_dead_letter \
+                 WHERE guild = $1 ORDER BY id",
+                &[&gid],
+            )
+            .await
+            .map_err(|_| error::Error::Fatal)?;
+        rows.into_iter()
+            .map(|row| {
+                let url = row.try_get("url").map_err(|_| error::Error::Fatal)?;
+                let payload = row.try_get("payload").map_err(|_| error::Error::Fatal)?;
+                let attempts: i16 = row.try_get("attempts").map_err(|_| error::Error::Fatal)?;
+                let attempts = u16::try_from(attempts).map_err(|_| error::Error::Fatal)?;
+                let error = row.try_get("error").map_err(|_| error::Error::Fatal)?;
+                Ok(DeadLetter { url, payload, attempts, error })
+            })
+            .collect()
+    }
+
+    async fn get_guild_settings(&self, guild: NonZeroU64) -> error::Result<GuildSettings> {
+        let gid = guild.get() as i64;
+        let Some(row) = self
+            .client
+            .query_opt(
+                "SELECT expiration, color, ping_winners, answer_changes, channels FROM guild_settings WHERE guild = $1",
+                &[&gid],
+            )
+            .await
+            .map_err(|_| error::Error::Fatal)?
+        else {
+            return Ok(GuildSettings::default());
+        };
 
-        Err(error::Error::BadInput)
+        let expiration: Option<i16> = row.try_get("expiration").map_err(|_| error::Error::Fatal)?;
+        let color: Option<i32> = row.try_get("color").map_err(|_| error::Error::Fatal)?;
+        let answer_changes: Option<i16> = row.try_get("answer_changes").map_err(|_| error::Error::Fatal)?;
+        let channels: Vec<i64> = row.try_get("channels").map_err(|_| error::Error::Fatal)?;
+        Ok(GuildSettings {
+            expiration: expiration.map(u16::try_from).transpose().map_err(|_| error::Error::Fatal)?,
+            color: color.map(u32::try_from).transpose().map_err(|_| error::Error::Fatal)?,
+            ping_winners: row.try_get("ping_winners").map_err(|_| error::Error::Fatal)?,
+            answer_policy: AnswerPolicy::from_changes(
+                answer_changes.map(u16::try_from).transpose().map_err(|_| error::Error::Fatal)?,
+            ),
+            channels: channels
+                .into_iter()
+                .map(|channel| NonZeroU64::new(channel as u64).ok_or(error::Error::Fatal))
+                .collect::<error::Result<_>>()?,
+        })
+    }
+
+    async fn set_guild_settings(&self, guild: NonZeroU64, settings: &GuildSettings) -> error::Result<()> {
+        let gid = guild.get() as i64;
+        let GuildSettings { expiration, color, ping_winners, answer_policy, channels } = settings;
+        let expiration =
+            expiration.map(i16::try_from).transpose().map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let color = color.map(i32::try_from).transpose().map_err(|_| error::Error::BadInput(Constraint::Color))?;
+        let answer_changes = answer_policy.changes().map(i16::try_from).transpose();
+        let answer_changes = answer_changes.map_err(|_| error::Error::BadInput(Constraint::AnswerChanges))?;
+        let channels: Vec<_> = channels.iter().map(|channel| channel.get() as i64).collect();
+        let err = match self
+            .client
+            .execute(
+                "INSERT INTO guild_settings (guild, expiration, color, ping_winners, answer_changes, channels) \
+                 VALUES ($1, $2, $3, $4, $5, $6) \
+                 ON CONFLICT (guild) DO UPDATE SET expiration = EXCLUDED.expiration, color = EXCLUDED.color, \
+                 ping_winners = EXCLUDED.ping_winners, answer_changes = EXCLUDED.answer_changes, \
+                 channels = EXCLUDED.channels",
+                &[&gid, &expiration, &color, ping_winners, &answer_changes, &channels],
+            )
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        let err = err.as_db_error().ok_or(error::Error::Fatal)?;
+        Err(match (err.code(), err.constraint()) {
+            // We tried to set an expiration outside the allowed range.
+            (&SqlState::CHECK_VIOLATION, Some("guild_settings_expiration_check")) => {
+                error::Error::BadInput(Constraint::Expiration)
+            }
+            // We tried to set a color that is not 24-bit RGB.
+            (&SqlState::CHECK_VIOLATION, Some("guild_settings_color_check")) => {
+                error::Error::BadInput(Constraint::Color)
+            }
+            // We tried to allow too many answer changes.
+            (&SqlState::CHECK_VIOLATION, Some("guild_settings_answer_changes_check")) => {
+                error::Error::BadInput(Constraint::AnswerChanges)
+            }
+            // We tried to allow too many channels.
+            (&SqlState::CHECK_VIOLATION, Some("guild_settings_channels_length_check")) => {
+                error::Error::BadInput(Constraint::ChannelCount)
+            }
+            // Unexpected error type.
+            _ => error::Error::Fatal,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, Database, NoTls, NonZeroU64, Quiz, TryStreamExt};
+    use super::{
+        error::{Constraint, Error},
+        memory::InMemory,
+        AnswerPolicy, Config, Database, DeadLetter, GuildSettings, NoTls, NonZeroI16, NonZeroU16, NonZeroU64, Quiz,
+        QuizPatch, QuizStore, Quota, TryStreamExt, Webhook,
+    };
 
-    #[tokio::test(flavor = "current_thread")]
-    async fn database_test() {
-        use std::env::var;
-        let user = var("PG_USERNAME").unwrap();
-        let pass = var("PG_PASSWORD").unwrap();
-        let host = var("PG_HOSTNAME").unwrap();
-        let data = var("PG_DATABASE").unwrap();
+    fn constraint(err: Error) -> Option<Constraint> {
+        match err {
+            Error::BadInput(constraint) => Some(constraint),
+            _ => None,
+        }
+    }
 
-        // Dummy credentials for the database
-        let (client, conn) = Config::new()
-            .user(&user)
-            .password(&pass)
-            .host(&host)
-            .dbname(&data)
-            .port(5432)
-            .connect(NoTls)
-            .await
-            .expect("cannot connect to database");
-        let handle = tokio::spawn(conn);
-        let db = Database::from(client);
+    /// Every test below stays within this quota.
+    const QUOTA: Quota = Quota { per_user: Some(2), per_guild: Some(3) };
+
+    async fn quiz_lifecycle(db: &impl QuizStore) {
+        db.ping().await.unwrap();
 
         // Quiz creation
         let uid = NonZeroU64::new(10).unwrap();
-        let qid = db.init_quiz(uid, "Hello world?").await.unwrap();
+        let qid = db.init_quiz(uid, None, "Hello world?", 10).await.unwrap();
 
         // Initial quiz retrieval
         let init = db.get_quiz(uid, qid).await.unwrap();
@@ -322,8 +905,345 @@ mod tests {
         // Verify that the quiz has been removed
         let quizzes: Vec<_> = db.get_quizzes_by_user(uid).await.unwrap().try_collect().await.unwrap();
         assert!(quizzes.is_empty());
+    }
+
+    async fn quiz_constraints(db: &impl QuizStore) {
+        let uid = NonZeroU64::new(11).unwrap();
+        let other = NonZeroU64::new(12).unwrap();
+        let long = "a".repeat(101);
+
+        // Question constraints
+        assert!(matches!(db.init_quiz(uid, None, "", 10).await, Err(Error::BadInput(Constraint::QuestionLength))));
+        assert!(matches!(db.init_quiz(uid, None, &long, 10).await, Err(Error::BadInput(Constraint::QuestionLength))));
+        assert!(matches!(db.init_quiz(uid, None, "Too short?", 9).await, Err(Error::BadInput(Constraint::Expiration))));
+        assert!(matches!(
+            db.init_quiz(uid, None, "Too long?", 601).await,
+            Err(Error::BadInput(Constraint::Expiration))
+        ));
+        let qid = db.init_quiz(uid, None, &long[..100], 600).await.unwrap();
+        assert!(matches!(db.set_question(uid, qid, "").await, Err(Error::BadInput(Constraint::QuestionLength))));
+        assert!(matches!(db.set_question(uid, qid, &long).await, Err(Error::BadInput(Constraint::QuestionLength))));
+
+        // Quizzes are only visible to their author
+        assert!(matches!(db.get_quiz(other, qid).await, Err(Error::NotFound)));
+        assert!(matches!(db.set_question(other, qid, "Hijacked?").await, Err(Error::NotFound)));
+        assert!(matches!(db.add_choice(other, qid, "Hijacked").await, Err(Error::NotFound)));
+        assert!(matches!(db.remove_choice(other, qid, 0).await, Err(Error::NotFound)));
+        assert!(matches!(db.set_answer(other, qid, 0).await, Err(Error::NotFound)));
+        assert!(matches!(db.set_expiration(other, qid, 20).await, Err(Error::NotFound)));
+        assert!(matches!(db.pop_quiz(other, qid).await, Err(Error::NotFound)));
+
+        // Expiration constraints
+        assert!(matches!(db.set_expiration(uid, qid, 9).await, Err(Error::BadInput(Constraint::Expiration))));
+        assert!(matches!(db.set_expiration(uid, qid, 601).await, Err(Error::BadInput(Constraint::Expiration))));
+        assert!(matches!(db.set_expiration(uid, qid, u16::MAX).await, Err(Error::BadInput(Constraint::Expiration))));
+        db.set_expiration(uid, qid, 600).await.unwrap();
+
+        // Quizzes without an answer cannot be started, but drafts can still be removed
+        assert!(matches!(db.pop_quiz(uid, qid).await, Err(Error::NotFound)));
+        let draft = db.init_quiz(uid, None, "Draft?", 10).await.unwrap();
+        assert!(matches!(db.remove_quiz(other, draft).await, Err(Error::NotFound)));
+        assert_eq!(db.remove_quiz(uid, draft).await.unwrap().question, "Draft?");
+        assert!(matches!(db.get_quiz(uid, draft).await, Err(Error::NotFound)));
+
+        // Answers cannot be set before there are choices to pick from
+        let no_choices = Some(Constraint::AnswerRange { choices: 0 });
+        assert_eq!(db.set_answer(uid, qid, 0).await.err().and_then(constraint), no_choices);
+        let patch = QuizPatch { question: Some("Patched?".into()), answer: Some(0), ..QuizPatch::default() };
+        assert_eq!(db.update_quiz(uid, qid, &patch).await.err().and_then(constraint), no_choices);
+        let raw = db.get_quiz(uid, qid).await.unwrap();
+        assert!(raw.answer.is_none());
+        assert_eq!(raw.question, &long[..100]);
+
+        // Choice constraints
+        assert!(matches!(db.add_choice(uid, qid, &long).await, Err(Error::BadInput(Constraint::ChoiceLength))));
+        for choice in 0..25 {
+            db.add_choice(uid, qid, &choice.to_string()).await.unwrap();
+        }
+        assert!(matches!(db.add_choice(uid, qid, "Overflow").await, Err(Error::BadInput(Constraint::ChoiceCount))));
+
+        // Answer constraints
+        assert!(matches!(
+            db.set_answer(uid, qid, 25).await,
+            Err(Error::BadInput(Constraint::AnswerRange { choices: 25 }))
+        ));
+        assert!(matches!(
+            db.set_answer(uid, qid, u16::MAX).await,
+            Err(Error::BadInput(Constraint::AnswerRange { choices: 25 }))
+        ));
+        db.set_answer(uid, qid, 24).await.unwrap();
+
+        // Patches apply all of their fields or none at all
+        let before = db.get_quiz(uid, qid).await.unwrap();
+        let patch = QuizPatch { question: Some("Patched?".into()), expiration: Some(30), answer: Some(25) };
+        let out_of_range = Some(Constraint::AnswerRange { choices: 25 });
+        assert_eq!(db.update_quiz(uid, qid, &patch).await.err().and_then(constraint), out_of_range);
+        let patch = QuizPatch { expiration: Some(601), answer: Some(0), ..patch };
+        assert_eq!(db.update_quiz(uid, qid, &patch).await.err().and_then(constraint), Some(Constraint::Expiration));
+        let patch = QuizPatch { question: Some(String::new()), expiration: Some(30), ..patch };
+        assert_eq!(db.update_quiz(uid, qid, &patch).await.err().and_then(constraint), Some(Constraint::QuestionLength));
+        assert!(matches!(db.update_quiz(other, qid, &QuizPatch::default()).await, Err(Error::NotFound)));
+        assert_eq!(db.get_quiz(uid, qid).await.unwrap(), before);
+        db.update_quiz(uid, qid, &QuizPatch { answer: Some(0), ..QuizPatch::default() }).await.unwrap();
+        assert_eq!(db.get_quiz(uid, qid).await.unwrap().answer, Some(0));
+        db.set_answer(uid, qid, 24).await.unwrap();
+
+        // Removing a choice resets the answer, even if the index is out of range
+        let out_of_range = Some(Constraint::ChoiceIndex { choices: 25 });
+        assert_eq!(db.remove_choice(uid, qid, 25).await.err().and_then(constraint), out_of_range);
+        assert_eq!(db.remove_choice(uid, qid, u32::MAX).await.err().and_then(constraint), out_of_range);
+        assert!(db.get_quiz(uid, qid).await.unwrap().answer.is_none());
+        assert_eq!(db.remove_choice(uid, qid, 0).await.unwrap().as_ref(), "0");
+        db.set_answer(uid, qid, 23).await.unwrap();
+        assert!(matches!(
+            db.set_answer(uid, qid, 24).await,
+            Err(Error::BadInput(Constraint::AnswerRange { choices: 24 }))
+        ));
+
+        db.set_answer(uid, qid, 0).await.unwrap();
+        let quiz = db.pop_quiz(uid, qid).await.unwrap();
+        assert_eq!(quiz.question, long[..100]);
+        assert_eq!(quiz.choices.len(), 24);
+        assert_eq!(quiz.expiration, 600);
+        assert!(matches!(db.get_quiz(uid, qid).await, Err(Error::NotFound)));
+    }
+
+    async fn quotas(db: &impl QuizStore) {
+        let uid = NonZeroU64::new(50).unwrap();
+        let other = NonZeroU64::new(51).unwrap();
+        let guild = NonZeroU64::new(60).unwrap();
+        let mut quizzes = Vec::new();
+
+        // Each author is limited regardless of the guild
+        quizzes.push((uid, db.init_quiz(uid, Some(guild), "First?", 10).await.unwrap()));
+        quizzes.push((uid, db.init_quiz(uid, Some(guild), "Second?", 10).await.unwrap()));
+        assert!(matches!(
+            db.init_quiz(uid, None, "Third?", 10).await,
+            Err(Error::QuotaExceeded { limit: 2, guild: false })
+        ));
+
+        // The guild is limited across all of its authors
+        quizzes.push((other, db.init_quiz(other, Some(guild), "Third?", 10).await.unwrap()));
+        let result = db.init_quiz(other, Some(guild), "Fourth?", 10).await;
+        assert!(matches!(result, Err(Error::QuotaExceeded { limit: 3, guild: true })));
+        quizzes.push((other, db.init_quiz(other, None, "Elsewhere?", 10).await.unwrap()));
+
+        // Starting a quiz frees up its slot
+        let (_, first) = quizzes.remove(0);
+        pop(db, uid, first).await;
+        quizzes.push((uid, db.init_quiz(uid, Some(guild), "Again?", 10).await.unwrap()));
+        for (user, quiz) in quizzes {
+            pop(db, user, quiz).await;
+        }
+    }
+
+    /// Races one more quiz than the per-user quota allows, each over its own connection.
+    async fn concurrent_quotas(db: &Database, config: &Config) {
+        let uid = NonZeroU64::new(70).unwrap();
+        let limit = QUOTA.per_user.unwrap();
+
+        // Connect everyone first so that the insertions actually overlap
+        let mut racers = Vec::new();
+        for _ in 0..=limit {
+            let (client, conn) = config.connect(NoTls).await.expect("cannot connect to database");
+            tokio::spawn(conn);
+            racers.push(Database::from(client).with_quota(QUOTA));
+        }
+        let tasks: Vec<_> = racers
+            .into_iter()
+            .map(|racer| tokio::spawn(async move { racer.init_quiz(uid, None, "Race?", 10).await }))
+            .collect();
+
+        let mut quizzes = Vec::new();
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(quiz) => quizzes.push(quiz),
+                Err(err) => assert!(matches!(err, Error::QuotaExceeded { limit: 2, guild: false })),
+            }
+        }
+        assert_eq!(quizzes.len(), usize::from(limit));
+        for quiz in quizzes {
+            pop(db, uid, quiz).await;
+        }
+    }
+
+    /// Removes a quiz by starting it.
+    async fn pop(db: &impl QuizStore, user: NonZeroU64, quiz: NonZeroI16) {
+        db.add_choice(user, quiz, "Yes").await.unwrap();
+        db.set_answer(user, quiz, 0).await.unwrap();
+        db.pop_quiz(user, quiz).await.unwrap();
+    }
+
+    async fn api_keys(db: &impl QuizStore) {
+        let uid = NonZeroU64::new(30).unwrap();
+        let other = NonZeroU64::new(31).unwrap();
+        assert!(matches!(db.get_user_by_api_key([1; 32]).await, Err(Error::NotFound)));
+
+        // Each user holds a single key, so issuing another replaces the first
+        db.set_api_key(uid, [1; 32]).await.unwrap();
+        db.set_api_key(other, [2; 32]).await.unwrap();
+        assert_eq!(db.get_user_by_api_key([1; 32]).await.unwrap(), uid);
+        db.set_api_key(uid, [3; 32]).await.unwrap();
+        assert!(matches!(db.get_user_by_api_key([1; 32]).await, Err(Error::NotFound)));
+        assert_eq!(db.get_user_by_api_key([3; 32]).await.unwrap(), uid);
+
+        db.remove_api_key(uid).await.unwrap();
+        db.remove_api_key(other).await.unwrap();
+        assert!(matches!(db.get_user_by_api_key([3; 32]).await, Err(Error::NotFound)));
+        assert!(matches!(db.remove_api_key(uid).await, Err(Error::NotFound)));
+    }
+
+    async fn webhooks(db: &impl QuizStore) {
+        let guild = NonZeroU64::new(40).unwrap();
+        let other = NonZeroU64::new(41).unwrap();
+        assert!(db.get_webhooks(guild).await.unwrap().is_empty());
+
+        // Setting the same URL again only replaces its secret
+        db.set_webhook(guild, "https://lms.example.com/b", "one").await.unwrap();
+        db.set_webhook(guild, "https://lms.example.com/a", "two").await.unwrap();
+        db.set_webhook(guild, "https://lms.example.com/b", "three").await.unwrap();
+        db.set_webhook(other, "https://lms.example.com/a", "four").await.unwrap();
+        let hooks = db.get_webhooks(guild).await.unwrap();
+        assert_eq!(
+            hooks,
+            [
+                Webhook { url: "https://lms.example.com/a".into(), secret: "two".into() },
+                Webhook { url: "https://lms.example.com/b".into(), secret: "three".into() },
+            ]
+        );
+
+        // Webhook constraints
+        assert!(matches!(
+            db.set_webhook(guild, "ftp://lms.example.com", "secret").await,
+            Err(Error::BadInput(Constraint::WebhookUrl))
+        ));
+        assert!(matches!(
+            db.set_webhook(guild, "https://lms.example.com", "").await,
+            Err(Error::BadInput(Constraint::WebhookSecret))
+        ));
+        let long = format!("https://lms.example.com/{}", "a".repeat(2048));
+        assert!(matches!(db.set_webhook(guild, &long, "secret").await, Err(Error::BadInput(Constraint::WebhookUrl))));
+
+        db.remove_webhook(guild, "https://lms.example.com/a").await.unwrap();
+        db.remove_webhook(guild, "https://lms.example.com/b").await.unwrap();
+        db.remove_webhook(other, "https://lms.example.com/a").await.unwrap();
+        assert!(matches!(db.remove_webhook(guild, "https://lms.example.com/a").await, Err(Error::NotFound)));
+
+        // Dead letters outlive their webhook
+        let letter = DeadLetter {
+            url: "https://lms.example.com/a".into(),
+            payload: r#"{"event":"quiz.finished"}"#.into(),
+            attempts: 5,
+            error: "status 503".into(),
+        };
+        db.add_dead_letter(guild, &letter).await.unwrap();
+        assert_eq!(db.get_dead_letters(guild).await.unwrap().last(), Some(&letter));
+        assert!(db.get_dead_letters(other).await.unwrap().is_empty());
+    }
+
+    async fn guild_settings(db: &impl QuizStore) {
+        let guild = NonZeroU64::new(30).unwrap();
+        let other = NonZeroU64::new(31).unwrap();
+        assert_eq!(db.get_guild_settings(other).await.unwrap(), GuildSettings::default());
+
+        let channels = vec![NonZeroU64::new(300).unwrap(), NonZeroU64::new(301).unwrap()];
+        let settings = GuildSettings {
+            expiration: Some(60),
+            color: Some(0xFF_FFFF),
+            ping_winners: false,
+            answer_policy: AnswerPolicy::Limited(NonZeroU16::new(10).unwrap()),
+            channels: channels.clone(),
+        };
+        db.set_guild_settings(guild, &settings).await.unwrap();
+        assert_eq!(db.get_guild_settings(guild).await.unwrap(), settings);
+        assert_eq!(db.get_guild_settings(other).await.unwrap(), GuildSettings::default());
+
+        // Settings constraints
+        let invalid = [
+            (GuildSettings { expiration: Some(9), ..settings.clone() }, Constraint::Expiration),
+            (GuildSettings { expiration: Some(u16::MAX), ..settings.clone() }, Constraint::Expiration),
+            (GuildSettings { color: Some(0x100_0000), ..settings.clone() }, Constraint::Color),
+            (GuildSettings { color: Some(u32::MAX), ..settings.clone() }, Constraint::Color),
+            (GuildSettings { channels: channels.repeat(13), ..settings.clone() }, Constraint::ChannelCount),
+            (
+                GuildSettings { answer_policy: AnswerPolicy::from_changes(Some(11)), ..settings.clone() },
+                Constraint::AnswerChanges,
+            ),
+            (
+                GuildSettings { answer_policy: AnswerPolicy::from_changes(Some(u16::MAX)), ..settings.clone() },
+                Constraint::AnswerChanges,
+            ),
+        ];
+        for (invalid, expected) in invalid {
+            assert_eq!(db.set_guild_settings(guild, &invalid).await.err().and_then(constraint), Some(expected));
+        }
+        assert_eq!(db.get_guild_settings(guild).await.unwrap(), settings);
+
+        db.set_guild_settings(guild, &GuildSettings::default()).await.unwrap();
+        assert_eq!(db.get_guild_settings(guild).await.unwrap(), GuildSettings::default());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn database_test() {
+        use std::env::var;
+        let user = var("PG_USERNAME").unwrap();
+        let pass = var("PG_PASSWORD").unwrap();
+        let host = var("PG_HOSTNAME").unwrap();
+        let data = var("PG_DATABASE").unwrap();
+
+        // Dummy credentials for the database
+        let mut config = Config::new();
+        config.user(&user).password(&pass).host(&host).dbname(&data).port(5432);
+        let (client, conn) = config.connect(NoTls).await.expect("cannot connect to database");
+        let handle = tokio::spawn(conn);
+        let db = Database::from(client).with_quota(QUOTA);
+
+        quiz_lifecycle(&db).await;
+        quiz_constraints(&db).await;
+        quotas(&db).await;
+        concurrent_quotas(&db, &config).await;
+        api_keys(&db).await;
+        webhooks(&db).await;
+        guild_settings(&db).await;
 
         drop(db);
         handle.await.unwrap().unwrap();
     }
+
+    #[cfg(feature = "rustls")]
+    #[tokio::test(flavor = "current_thread")]
+    async fn tls_test() {
+        use super::tls::{PgUrl, SslMode};
+        use std::env::var;
+        let mut config = Config::new();
+        config
+            .user(var("PG_USERNAME").unwrap())
+            .password(var("PG_PASSWORD").unwrap())
+            .host(var("PG_HOSTNAME").unwrap())
+            .dbname(var("PG_DATABASE").unwrap())
+            .port(5432)
+            .ssl_mode(tokio_postgres::config::SslMode::Require);
+
+        // Like libpq, `require` does not verify the certificate, so a self-signed one is enough
+        let url = PgUrl { config, mode: SslMode::Require, root_cert: None };
+        let (client, conn) = url.config.connect(url.connector().unwrap()).await.expect("cannot connect over TLS");
+        let handle = tokio::spawn(conn);
+
+        let row = client.query_one("SELECT ssl FROM pg_stat_ssl WHERE pid = pg_backend_pid()", &[]).await.unwrap();
+        assert!(row.get::<_, bool>(0));
+
+        drop(client);
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn memory_test() {
+        let db = InMemory::default().with_quota(QUOTA);
+        quiz_lifecycle(&db).await;
+        quiz_constraints(&db).await;
+        quotas(&db).await;
+        api_keys(&db).await;
+        webhooks(&db).await;
+        guild_settings(&db).await;
+    }
 }
@@ -0,0 +1,343 @@
+//! In-memory [`QuizStore`] for tests and local experimentation.
+
+use crate::{
+    error::{self, Constraint},
+    DeadLetter, GuildSettings, Quiz, QuizPatch, QuizStore, Quota, RawQuiz, TryStream, Webhook,
+};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::num::{NonZeroI16, NonZeroU64};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Default)]
+struct State {
+    /// Last value produced by the `SMALLSERIAL` sequence.
+    serial: i16,
+    /// All quizzes keyed by their author and ID.
+    quizzes: BTreeMap<(NonZeroU64, NonZeroI16), RawQuiz>,
+    /// Guilds of the quizzes that were created in one, keyed like `quizzes`.
+    guilds: BTreeMap<(NonZeroU64, NonZeroI16), NonZeroU64>,
+    /// SHA-256 digests of API keys keyed by their owner.
+    api_keys: BTreeMap<NonZeroU64, [u8; 32]>,
+    /// Webhook secrets keyed by their guild and URL.
+    webhooks: BTreeMap<(NonZeroU64, String), String>,
+    /// Undelivered payloads in insertion order.
+    dead_letters: Vec<(NonZeroU64, DeadLetter)>,
+    /// Settings of the guilds that changed them.
+    guild_settings: BTreeMap<NonZeroU64, GuildSettings>,
+}
+
+/// Mirrors the PostgreSQL schema (including its quirks) without a live database.
+#[derive(Default)]
+pub struct InMemory {
+    state: Mutex<State>,
+    quota: Quota,
+}
+
+fn check_text(text: &str, constraint: Constraint) -> error::Result<()> {
+    if text.chars().count() > Constraint::MAX_TEXT_LEN {
+        Err(error::Error::BadInput(constraint))
+    } else {
+        Ok(())
+    }
+}
+
+fn in_expiration_range(expiration: i16) -> bool {
+    u16::try_from(expiration).is_ok_and(|expiration| Constraint::EXPIRATION.contains(&expiration))
+}
+
+impl InMemory {
+    /// Limits how many quizzes may be stored at once. Defaults to [`Quota::default`].
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    fn lock(&self) -> error::Result<MutexGuard<'_, State>> {
+        self.state.lock().map_err(|_| error::Error::Fatal)
+    }
+}
+
+impl QuizStore for InMemory {
+    async fn ping(&self) -> error::Result<()> {
+        self.lock().map(drop)
+    }
+
+    async fn init_quiz(
+        &self,
+        user: NonZeroU64,
+        guild: Option<NonZeroU64>,
+        question: &str,
+        expiration: u16,
+    ) -> error::Result<NonZeroI16> {
+        let expiration = i16::try_from(expiration).map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let mut state = self.lock()?;
+
+        // The quota is checked before any row is produced, so a rejected quiz does not advance the sequence.
+        if let Some(limit) = self.quota.per_user {
+            if state.quizzes.keys().filter(|&&(author, _)| author == user).count() >= usize::from(limit) {
+                return Err(error::Error::QuotaExceeded { limit, guild: false });
+            }
+        }
+        if let Some((limit, guild)) = self.quota.per_guild.zip(guild) {
+            if state.guilds.values().filter(|&&other| other == guild).count() >= usize::from(limit) {
+                return Err(error::Error::QuotaExceeded { limit, guild: true });
+            }
+        }
+
+        // Like `nextval`, the sequence advances even if the row is later rejected.
+        let serial = state.serial.checked_add(1).ok_or(error::Error::Fatal)?;
+        state.serial = serial;
+        let id = NonZeroI16::new(serial).ok_or(error::Error::Fatal)?;
+
+        // PostgreSQL truncates before it runs the `CHECK` constraints in alphabetical order.
+        check_text(question, Constraint::QuestionLength)?;
+        if !in_expiration_range(expiration) {
+            return Err(error::Error::BadInput(Constraint::Expiration));
+        }
+        if question.is_empty() {
+            return Err(error::Error::BadInput(Constraint::QuestionLength));
+        }
+
+        let quiz = RawQuiz { question: question.into(), choices: Vec::new(), answer: None, expiration };
+        state.quizzes.insert((user, id), quiz);
+        if let Some(guild) = guild {
+            state.guilds.insert((user, id), guild);
+        }
+        Ok(id)
+    }
+
+    async fn get_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        self.lock()?.quizzes.get(&(user, quiz)).cloned().ok_or(error::Error::NotFound)
+    }
+
+    async fn get_quizzes_by_user(
+        &self,
+        user: NonZeroU64,
+    ) -> error::Result<impl TryStream<Ok = Quiz, Error = error::Error> + Send + '_> {
+        let start = (user, NonZeroI16::MIN);
+        let end = (user, NonZeroI16::MAX);
+        let quizzes: Vec<_> =
+            self.lock()?.quizzes.range(start..=end).map(|(&(_, id), raw)| Ok(Quiz { id, raw: raw.clone() })).collect();
+        Ok(futures_util::stream::iter(quizzes))
+    }
+
+    async fn pop_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        let mut state = self.lock()?;
+        let key = (user, quiz);
+        match state.quizzes.get(&key) {
+            Some(RawQuiz { answer: Some(_), .. }) => {
+                state.guilds.remove(&key);
+                state.quizzes.remove(&key).ok_or(error::Error::Fatal)
+            }
+            _ => Err(error::Error::NotFound),
+        }
+    }
+
+    async fn remove_quiz(&self, user: NonZeroU64, quiz: NonZeroI16) -> error::Result<RawQuiz> {
+        let mut state = self.lock()?;
+        let key = (user, quiz);
+        state.guilds.remove(&key);
+        state.quizzes.remove(&key).ok_or(error::Error::NotFound)
+    }
+
+    async fn add_choice(&self, user: NonZeroU64, quiz: NonZeroI16, choice: &str) -> error::Result<()> {
+        let mut state = self.lock()?;
+        let RawQuiz { choices, .. } = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        check_text(choice, Constraint::ChoiceLength)?;
+        if choices.len() >= Constraint::MAX_CHOICES {
+            return Err(error::Error::BadInput(Constraint::ChoiceCount));
+        }
+        choices.push(choice.into());
+        Ok(())
+    }
+
+    async fn remove_choice(&self, user: NonZeroU64, quiz: NonZeroI16, index: u32) -> error::Result<Box<str>> {
+        let mut state = self.lock()?;
+        let RawQuiz { choices, answer, .. } = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        let out_of_range =
+            Constraint::ChoiceIndex { choices: u16::try_from(choices.len()).map_err(|_| error::Error::Fatal)? };
+        let index = i32::try_from(index).map_err(|_| error::Error::BadInput(out_of_range))?;
+        let index = usize::try_from(index).map_err(|_| error::Error::Fatal)?;
+
+        // The answer is reset even if the index is out of bounds, in which case PostgreSQL returns a `NULL` choice.
+        *answer = None;
+        if index >= choices.len() {
+            return Err(error::Error::BadInput(out_of_range));
+        }
+
+        let choice: String = choices.remove(index);
+        Ok(choice.into_boxed_str())
+    }
+
+    async fn set_question(&self, user: NonZeroU64, quiz: NonZeroI16, question: &str) -> error::Result<()> {
+        let mut state = self.lock()?;
+        let raw = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        check_text(question, Constraint::QuestionLength)?;
+        if question.is_empty() {
+            return Err(error::Error::BadInput(Constraint::QuestionLength));
+        }
+        raw.question = question.into();
+        Ok(())
+    }
+
+    async fn set_answer(&self, user: NonZeroU64, quiz: NonZeroI16, answer: u16) -> error::Result<()> {
+        let mut state = self.lock()?;
+        let raw = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        let len = raw.choices.len();
+        let out_of_range = Constraint::AnswerRange { choices: u16::try_from(len).map_err(|_| error::Error::Fatal)? };
+        let answer = i16::try_from(answer).map_err(|_| error::Error::BadInput(out_of_range))?;
+
+        if usize::try_from(answer).map_or(true, |answer| answer >= len) {
+            return Err(error::Error::BadInput(out_of_range));
+        }
+
+        raw.answer = Some(answer);
+        Ok(())
+    }
+
+    async fn set_expiration(&self, user: NonZeroU64, quiz: NonZeroI16, expiration: u16) -> error::Result<()> {
+        let expiration = i16::try_from(expiration).map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let mut state = self.lock()?;
+        let raw = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        if !in_expiration_range(expiration) {
+            return Err(error::Error::BadInput(Constraint::Expiration));
+        }
+        raw.expiration = expiration;
+        Ok(())
+    }
+
+    async fn update_quiz(&self, user: NonZeroU64, quiz: NonZeroI16, patch: &QuizPatch) -> error::Result<()> {
+        let expiration = patch
+            .expiration
+            .map(i16::try_from)
+            .transpose()
+            .map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let mut state = self.lock()?;
+        let raw = state.quizzes.get_mut(&(user, quiz)).ok_or(error::Error::NotFound)?;
+        let len = raw.choices.len();
+        let out_of_range = Constraint::AnswerRange { choices: u16::try_from(len).map_err(|_| error::Error::Fatal)? };
+        let answer = patch.answer.map(i16::try_from).transpose().map_err(|_| error::Error::BadInput(out_of_range))?;
+
+        // Quizzes without choices are skipped before PostgreSQL looks at any of the new values.
+        if answer.is_some() && len == 0 {
+            return Err(error::Error::BadInput(out_of_range));
+        }
+
+        // PostgreSQL truncates before it runs the `CHECK` constraints in alphabetical order.
+        if let Some(question) = &patch.question {
+            check_text(question, Constraint::QuestionLength)?;
+        }
+        if answer.is_some_and(|answer| usize::try_from(answer).map_or(true, |answer| answer >= len)) {
+            return Err(error::Error::BadInput(out_of_range));
+        }
+        if expiration.is_some_and(|expiration| !in_expiration_range(expiration)) {
+            return Err(error::Error::BadInput(Constraint::Expiration));
+        }
+        if patch.question.as_deref() == Some("") {
+            return Err(error::Error::BadInput(Constraint::QuestionLength));
+        }
+
+        if let Some(question) = &patch.question {
+            raw.question = question.as_str().into();
+        }
+        if let Some(expiration) = expiration {
+            raw.expiration = expiration;
+        }
+        if answer.is_some() {
+            raw.answer = answer;
+        }
+        Ok(())
+    }
+
+    async fn set_api_key(&self, user: NonZeroU64, digest: [u8; 32]) -> error::Result<()> {
+        let mut state = self.lock()?;
+        // Mirrors the `UNIQUE` constraint on the digest.
+        if state.api_keys.iter().any(|(&owner, &other)| owner != user && other == digest) {
+            return Err(error::Error::Fatal);
+        }
+        state.api_keys.insert(user, digest);
+        Ok(())
+    }
+
+    async fn remove_api_key(&self, user: NonZeroU64) -> error::Result<()> {
+        self.lock()?.api_keys.remove(&user).map(drop).ok_or(error::Error::NotFound)
+    }
+
+    async fn get_user_by_api_key(&self, digest: [u8; 32]) -> error::Result<NonZeroU64> {
+        let state = self.lock()?;
+        state
+            .api_keys
+            .iter()
+            .find_map(|(&owner, &other)| (other == digest).then_some(owner))
+            .ok_or(error::Error::NotFound)
+    }
+
+    async fn set_webhook(&self, guild: NonZeroU64, url: &str, secret: &str) -> error::Result<()> {
+        let is_http = url.starts_with("http://") || url.starts_with("https://");
+        if !is_http || url.chars().count() > Constraint::MAX_URL_LEN {
+            return Err(error::Error::BadInput(Constraint::WebhookUrl));
+        }
+        if secret.is_empty() || secret.chars().count() > Constraint::MAX_SECRET_LEN {
+            return Err(error::Error::BadInput(Constraint::WebhookSecret));
+        }
+        self.lock()?.webhooks.insert((guild, url.into()), secret.into());
+        Ok(())
+    }
+
+    async fn remove_webhook(&self, guild: NonZeroU64, url: &str) -> error::Result<()> {
+        self.lock()?.webhooks.remove(&(guild, url.into())).map(drop).ok_or(error::Error::NotFound)
+    }
+
+    async fn get_webhooks(&self, guild: NonZeroU64) -> error::Result<Vec<Webhook>> {
+        let state = self.lock()?;
+        let webhooks = state
+            .webhooks
+            .iter()
+            .filter(|((owner, _), _)| *owner == guild)
+            .map(|((_, url), secret)| Webhook { url: url.clone(), secret: secret.clone() })
+            .collect();
+        Ok(webhooks)
+    }
+
+    async fn add_dead_letter(&self, guild: NonZeroU64, letter: &DeadLetter) -> error::Result<()> {
+        if letter.attempts == 0 {
+            return Err(error::Error::BadInput(Constraint::DeadLetterAttempts));
+        }
+        self.lock()?.dead_letters.push((guild, letter.clone()));
+        Ok(())
+    }
+
+    async fn get_dead_letters(&self, guild: NonZeroU64) -> error::Result<Vec<DeadLetter>> {
+        let state = self.lock()?;
+        let letters = state.dead_letters.iter().filter(|(owner, _)| *owner == guild).map(|(_, letter)| letter.clone());
+        Ok(letters.collect())
+    }
+
+    async fn get_guild_settings(&self, guild: NonZeroU64) -> error::Result<GuildSettings> {
+        Ok(self.lock()?.guild_settings.get(&guild).cloned().unwrap_or_default())
+    }
+
+    async fn set_guild_settings(&self, guild: NonZeroU64, settings: &GuildSettings) -> error::Result<()> {
+        // Conversions come first, followed by the `CHECK` constraints in alphabetical order.
+        let expiration = settings.expiration.map(i16::try_from).transpose();
+        let expiration = expiration.map_err(|_| error::Error::BadInput(Constraint::Expiration))?;
+        let color = settings.color.map(i32::try_from).transpose();
+        let color = color.map_err(|_| error::Error::BadInput(Constraint::Color))?;
+        let answer_changes = settings.answer_policy.changes().map(i16::try_from).transpose();
+        let answer_changes = answer_changes.map_err(|_| error::Error::BadInput(Constraint::AnswerChanges))?;
+        if answer_changes.is_some_and(|changes| !(0..=Constraint::MAX_ANSWER_CHANGES as i16).contains(&changes)) {
+            return Err(error::Error::BadInput(Constraint::AnswerChanges));
+        }
+        if settings.channels.len() > Constraint::MAX_CHANNELS {
+            return Err(error::Error::BadInput(Constraint::ChannelCount));
+        }
+        if color.is_some_and(|color| !(0..=Constraint::MAX_COLOR as i32).contains(&color)) {
+            return Err(error::Error::BadInput(Constraint::Color));
+        }
+        if expiration.is_some_and(|expiration| !in_expiration_range(expiration)) {
+            return Err(error::Error::BadInput(Constraint::Expiration));
+        }
+        self.lock()?.guild_settings.insert(guild, settings.clone());
+        Ok(())
+    }
+}
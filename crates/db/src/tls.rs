@@ -0,0 +1,263 @@
+//! TLS for PostgreSQL connections via `rustls`, following the `sslmode` and `sslrootcert` semantics of libpq.
+
+use alloc::{borrow::Cow, string::String, sync::Arc, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        VerifierBuilderError, WebPkiServerVerifier,
+    },
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use std::path::PathBuf;
+use tokio_postgres::{config::SslMode as Transport, Config};
+
+pub use tokio_postgres_rustls::MakeRustlsConnect;
+
+/// How strictly the server is checked before any credentials are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslMode {
+    /// Never use TLS.
+    Disable,
+    /// Use TLS if the server supports it.
+    Prefer,
+    /// Always use TLS. The certificate is only verified when a root certificate is given.
+    Require,
+    /// Always use TLS and verify that the certificate chains up to a trusted root.
+    VerifyCa,
+    /// Like [`SslMode::VerifyCa`], but the certificate must also match the host name.
+    VerifyFull,
+}
+
+impl FromStr for SslMode {
+    type Err = Error;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "disable" => Self::Disable,
+            "prefer" => Self::Prefer,
+            "require" => Self::Require,
+            "verify-ca" => Self::VerifyCa,
+            "verify-full" => Self::VerifyFull,
+            _ => return Err(Error::SslMode(value.into())),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The connection string is malformed.
+    Config(tokio_postgres::Error),
+    /// The `sslmode` is not supported.
+    SslMode(String),
+    /// The root certificate bundle cannot be read.
+    RootCert(PathBuf, std::io::Error),
+    /// No trusted root certificates were found in the bundle (or the system store if `None`).
+    NoRootCerts(Option<PathBuf>),
+    /// The certificate verifier cannot be built from the root certificates.
+    Verifier(VerifierBuilderError),
+    /// The TLS configuration was rejected.
+    Rustls(rustls::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(err) => err.fmt(f),
+            Self::SslMode(mode) => write!(f, "unsupported sslmode `{mode}`"),
+            Self::RootCert(path, err) => write!(f, "cannot read root certificates from `{}`: {err}", path.display()),
+            Self::NoRootCerts(Some(path)) => write!(f, "no root certificates found in `{}`", path.display()),
+            Self::NoRootCerts(None) => f.write_str("no root certificates found in the system store"),
+            Self::Verifier(err) => err.fmt(f),
+            Self::Rustls(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A connection string that also understands the libpq parameters rejected by [`Config`], namely
+/// `sslmode=verify-ca`, `sslmode=verify-full` and `sslrootcert`. These are only recognized in URLs.
+pub struct PgUrl {
+    pub config: Config,
+    pub mode: SslMode,
+    /// PEM bundle of the only roots to trust. The system store is used if `None`.
+    pub root_cert: Option<PathBuf>,
+}
+
+impl FromStr for PgUrl {
+    type Err = Error;
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let is_url = url.starts_with("postgres://") || url.starts_with("postgresql://");
+        let (base, query) = match url.split_once('?') {
+            Some(parts) if is_url => parts,
+            _ => (url, ""),
+        };
+
+        let mut mode = None;
+        let mut root_cert = None;
+        let mut rest = Vec::new();
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            match param.split_once('=') {
+                Some(("sslmode", value)) => mode = Some(decode(value).parse()?),
+                Some(("sslrootcert", value)) => root_cert = Some(decode(value).into_owned().into()),
+                _ => rest.push(param),
+            }
+        }
+
+        let url = if rest.is_empty() { base.into() } else { alloc::format!("{base}?{}", rest.join("&")) };
+        let mut config: Config = url.parse().map_err(Error::Config)?;
+        let mode = mode.unwrap_or(match config.get_ssl_mode() {
+            Transport::Disable => SslMode::Disable,
+            Transport::Require => SslMode::Require,
+            _ => SslMode::Prefer,
+        });
+        config.ssl_mode(match mode {
+            SslMode::Disable => Transport::Disable,
+            SslMode::Prefer => Transport::Prefer,
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => Transport::Require,
+        });
+        Ok(Self { config, mode, root_cert })
+    }
+}
+
+impl PgUrl {
+    /// Builds a connector that checks the server as strictly as the [`SslMode`] demands.
+    pub fn connector(&self) -> Result<MakeRustlsConnect, Error> {
+        let provider = Arc::new(ring::default_provider());
+
+        // Like libpq, only verify the certificate of `require` connections if a root certificate was given
+        let verify = match self.mode {
+            SslMode::Disable | SslMode::Prefer | SslMode::Require => self.root_cert.is_some(),
+            SslMode::VerifyCa | SslMode::VerifyFull => true,
+        };
+        let webpki = if verify {
+            let roots = self.roots()?;
+            let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .map_err(Error::Verifier)?;
+            Some(verifier)
+        } else {
+            None
+        };
+
+        let hostname = self.mode == SslMode::VerifyFull;
+        let verifier = Verifier { webpki, hostname, provider: provider.clone() };
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(Error::Rustls)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        Ok(MakeRustlsConnect::new(config))
+    }
+
+    fn roots(&self) -> Result<RootCertStore, Error> {
+        let mut roots = RootCertStore::empty();
+        match &self.root_cert {
+            Some(path) => {
+                let pem = std::fs::read(path).map_err(|err| Error::RootCert(path.clone(), err))?;
+                let certs = rustls_pemfile::certs(&mut pem.as_slice())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| Error::RootCert(path.clone(), err))?;
+                roots.add_parsable_certificates(certs);
+            }
+            None => {
+                roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+            }
+        }
+
+        if roots.is_empty() {
+            return Err(Error::NoRootCerts(self.root_cert.clone()));
+        }
+        Ok(roots)
+    }
+}
+
+/// Decodes the `%XX` escapes of a URL query value.
+fn decode(value: &str) -> Cow<'_, str> {
+    percent_encoding::percent_decode_str(value).decode_utf8_lossy()
+}
+
+#[derive(Debug)]
+struct Verifier {
+    /// Checks the certificate chain, or accepts any certificate if `None`.
+    webpki: Option<Arc<WebPkiServerVerifier>>,
+    /// Whether the certificate must also match the host name.
+    hostname: bool,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for Verifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let Some(webpki) = &self.webpki else {
+            return Ok(ServerCertVerified::assertion());
+        };
+        match webpki.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) if !self.hostname => Ok(ServerCertVerified::assertion()),
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PgUrl, SslMode};
+    use tokio_postgres::config::SslMode as Transport;
+
+    #[test]
+    fn libpq_parameters() {
+        let url: PgUrl =
+            "postgres://user@db.example.com/quizzo?sslmode=verify-full&sslrootcert=%2Fetc%2Fca.pem&connect_timeout=5"
+                .parse()
+                .unwrap();
+        assert_eq!(url.mode, SslMode::VerifyFull);
+        assert_eq!(url.root_cert.as_deref(), Some("/etc/ca.pem".as_ref()));
+        assert_eq!(url.config.get_ssl_mode(), Transport::Require);
+        assert_eq!(url.config.get_connect_timeout(), Some(&core::time::Duration::from_secs(5)));
+
+        let url: PgUrl = "postgres://user@localhost/quizzo".parse().unwrap();
+        assert_eq!(url.mode, SslMode::Prefer);
+        assert!(url.root_cert.is_none());
+
+        let url: PgUrl = "host=localhost user=user sslmode=disable".parse().unwrap();
+        assert_eq!(url.mode, SslMode::Disable);
+
+        assert!("postgres://localhost?sslmode=allow".parse::<PgUrl>().is_err());
+    }
+}
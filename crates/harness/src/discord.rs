@@ -0,0 +1,109 @@
+//! Local stand-in for the Discord REST API.
+
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, Method, Response, StatusCode};
+use std::{
+    collections::VecDeque,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+use tokio::{net::TcpListener, sync::mpsc, task::JoinHandle};
+
+/// A request that the bot sent to the mock.
+#[derive(Debug)]
+pub struct Request {
+    pub method: Method,
+    /// Path relative to `/api/v10`.
+    pub path: String,
+    /// JSON body, the raw text of any other body (e.g., a form), or `Null` if the body was empty.
+    pub body: serde_json::Value,
+    /// The body exactly as received, e.g., for checking signatures.
+    pub raw: Bytes,
+    pub headers: hyper::HeaderMap,
+}
+
+type Script = Arc<Mutex<VecDeque<(StatusCode, serde_json::Value)>>>;
+
+pub struct MockDiscord {
+    addr: SocketAddr,
+    script: Script,
+    requests: mpsc::UnboundedReceiver<Request>,
+    server: JoinHandle<()>,
+}
+
+impl MockDiscord {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+        let (tx, requests) = mpsc::unbounded_channel();
+        let script = Script::default();
+        let server = tokio::spawn(serve(listener, tx, script.clone()));
+        Ok(Self { addr, script, requests, server })
+    }
+
+    /// Base URL to pass to [`api::AppBuilder::api_url`].
+    pub fn url(&self) -> api::ApiUrl {
+        format!("http://{}", self.addr).parse().unwrap()
+    }
+
+    /// Host and port of the mock, as in [`MockDiscord::url`].
+    pub fn url_host(&self) -> String {
+        self.addr.to_string()
+    }
+
+    /// Responds to the next request with `status` instead of `200 OK`. Calls are queued in order.
+    pub fn respond_with(&self, status: StatusCode) {
+        self.respond_json(status, serde_json::json!({}));
+    }
+
+    /// Like [`MockDiscord::respond_with`], but also replaces the empty JSON object in the body.
+    pub fn respond_json(&self, status: StatusCode, body: serde_json::Value) {
+        self.script.lock().unwrap().push_back((status, body));
+    }
+
+    /// Waits for the next request that the bot sends. Returns `None` on timeout.
+    pub async fn next_request(&mut self, timeout: core::time::Duration) -> Option<Request> {
+        tokio::time::timeout(timeout, self.requests.recv()).await.ok().flatten()
+    }
+}
+
+impl Drop for MockDiscord {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, tx: mpsc::UnboundedSender<Request>, script: Script) {
+    let http = hyper::server::conn::http1::Builder::new();
+    while let Ok((stream, _)) = listener.accept().await {
+        let tx = tx.clone();
+        let script = script.clone();
+        let service = hyper::service::service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+            let tx = tx.clone();
+            let script = script.clone();
+            async move {
+                let (hyper::http::request::Parts { method, uri, headers, .. }, body) = req.into_parts();
+                let raw = body.collect().await?.to_bytes();
+                let body = if raw.is_empty() {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::from_slice(&raw)
+                        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&raw).into_owned()))
+                };
+                let path = uri.path().trim_start_matches("/api/v10").to_owned();
+                tx.send(Request { method, path, body, raw, headers }).ok();
+
+                let next = script.lock().unwrap().pop_front();
+                let (status, body) = next.unwrap_or_else(|| (StatusCode::OK, serde_json::json!({})));
+                let mut response = Response::new(Full::new(Bytes::from(serde_json::to_vec(&body)?)));
+                *response.status_mut() = status;
+                response
+                    .headers_mut()
+                    .insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(response)
+            }
+        });
+        let io = hyper_util::rt::TokioIo::new(stream);
+        tokio::spawn(http.serve_connection(io, service));
+    }
+}
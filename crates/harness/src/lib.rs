@@ -0,0 +1,253 @@
+//! Offline simulator for driving Discord interactions through [`App::try_respond`].
+
+pub mod discord;
+
+use api::{App, AppBuilder, Ratelimiter};
+use core::{
+    fmt::Display,
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use db::memory::InMemory;
+use ed25519_dalek::{Signer, SigningKey};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Body, Bytes},
+    header::{HeaderName, HeaderValue, AUTHORIZATION},
+    HeaderMap, Method, Response, StatusCode,
+};
+use serde_json::{json, Value};
+
+pub use discord::MockDiscord;
+
+/// Application ID of the simulated bot.
+pub const APP_ID: u64 = 823813267133956136;
+
+pub struct Harness {
+    app: App<InMemory>,
+    key: SigningKey,
+    snowflake: AtomicU64,
+}
+
+impl Harness {
+    /// Creates a bot backed by an in-memory store that talks to the `discord` mock.
+    pub fn new(discord: &MockDiscord) -> Self {
+        Self::with(discord, |builder| builder)
+    }
+
+    /// Like [`Harness::new`], but lets the caller `configure` the app first.
+    pub fn with(discord: &MockDiscord, configure: impl FnOnce(AppBuilder) -> AppBuilder) -> Self {
+        Self::with_store(discord, InMemory::default(), configure)
+    }
+
+    /// Like [`Harness::with`], but backed by a custom `store` (e.g., with a different quota).
+    pub fn with_store(
+        discord: &MockDiscord,
+        store: InMemory,
+        configure: impl FnOnce(AppBuilder) -> AppBuilder,
+    ) -> Self {
+        let key = SigningKey::generate(&mut rand_core::OsRng);
+        let id = NonZeroU64::new(APP_ID).unwrap();
+        let builder = AppBuilder::new(id, "mock".into()).api_url(discord.url()).ratelimiter(Ratelimiter::Disabled);
+        let app = configure(builder).build(store, key.verifying_key());
+        Self { app, key, snowflake: AtomicU64::new(1) }
+    }
+
+    pub fn app(&self) -> &App<InMemory> {
+        &self.app
+    }
+
+    /// Generates a fresh snowflake for interaction IDs and the like.
+    pub fn next_id(&self) -> u64 {
+        self.snowflake.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends an arbitrary request through [`App::try_respond`].
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        headers: HeaderMap,
+        body: Vec<u8>,
+    ) -> Response<Full<Bytes>> {
+        self.request_body(method, path, headers, Full::new(Bytes::from(body))).await
+    }
+
+    /// Like [`Harness::request`], but with a custom (e.g., streaming) body.
+    pub async fn request_body<B>(
+        &self,
+        method: Method,
+        path: &str,
+        headers: HeaderMap,
+        body: B,
+    ) -> Response<Full<Bytes>>
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: Display,
+    {
+        let mut response = Response::default();
+        self.app.try_respond(&mut response, method, path, headers, body).await;
+        response
+    }
+
+    /// Calls the REST API with an optional bearer `key` and decodes the JSON reply.
+    pub async fn rest(
+        &self,
+        method: Method,
+        path: &str,
+        key: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {key}")).unwrap());
+        }
+        let body = body.map(|body| serde_json::to_vec(&body).unwrap()).unwrap_or_default();
+        let response = self.request(method, path, headers, body).await;
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    /// Posts `body` to `/discord` with the given signature headers.
+    pub async fn post(&self, timestamp: &str, signature: &str, body: Vec<u8>) -> Response<Full<Bytes>> {
+        self.request(Method::POST, "/discord", signature_headers(timestamp, signature), body).await
+    }
+
+    /// Signs `body` with the simulated Discord key, stamped with the given UNIX `timestamp`.
+    pub fn sign(&self, timestamp: &str, body: &[u8]) -> String {
+        let mut message = timestamp.as_bytes().to_vec();
+        message.extend_from_slice(body);
+        hex::encode(self.key.sign(&message).to_bytes())
+    }
+
+    /// Posts a correctly signed `payload` to `/discord`.
+    pub async fn send(&self, payload: &Value) -> Response<Full<Bytes>> {
+        let body = serde_json::to_vec(payload).unwrap();
+        let timestamp = now().to_string();
+        let signature = self.sign(&timestamp, &body);
+        self.post(&timestamp, &signature, body).await
+    }
+
+    /// Posts a correctly signed `payload` and decodes the JSON reply. Panics on any non-success status.
+    pub async fn interact(&self, payload: &Value) -> Value {
+        let response = self.send(payload).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    pub fn ping(&self) -> Value {
+        json!({
+            "id": self.next_id().to_string(),
+            "application_id": APP_ID.to_string(),
+            "type": 1,
+            "token": "ping",
+            "version": 1,
+        })
+    }
+
+    /// Builds a slash command invocation by `user`. The `options` follow Discord's wire format.
+    pub fn command(&self, user: u64, name: &str, options: Value) -> Value {
+        let id = self.next_id();
+        json!({
+            "id": id.to_string(),
+            "application_id": APP_ID.to_string(),
+            "type": 2,
+            "token": format!("token-{id}"),
+            "version": 1,
+            "channel": { "id": "1", "type": 1 },
+            "channel_id": "1",
+            "user": user_payload(user),
+            "data": {
+                "id": "1",
+                "name": name,
+                "type": 1,
+                "options": options,
+            },
+        })
+    }
+
+    /// Builds a select menu interaction where `user` picks `value` in the menu `custom_id`.
+    pub fn select(&self, user: u64, custom_id: &str, value: &str) -> Value {
+        let id = self.next_id();
+        json!({
+            "id": id.to_string(),
+            "application_id": APP_ID.to_string(),
+            "type": 3,
+            "token": format!("token-{id}"),
+            "version": 1,
+            "channel": { "id": "1", "type": 1 },
+            "channel_id": "1",
+            "user": user_payload(user),
+            "data": {
+                "custom_id": custom_id,
+                "component_type": 3,
+                "values": [value],
+            },
+        })
+    }
+
+    /// Builds a button interaction where `user` clicks the button `custom_id`.
+    pub fn click(&self, user: u64, custom_id: &str) -> Value {
+        let id = self.next_id();
+        json!({
+            "id": id.to_string(),
+            "application_id": APP_ID.to_string(),
+            "type": 3,
+            "token": format!("token-{id}"),
+            "version": 1,
+            "channel": { "id": "1", "type": 1 },
+            "channel_id": "1",
+            "user": user_payload(user),
+            "data": {
+                "custom_id": custom_id,
+                "component_type": 2,
+            },
+        })
+    }
+}
+
+/// Moves an interaction `payload` into `guild`, where Discord sends the invoking user as a member instead.
+pub fn in_guild(mut payload: Value, guild: u64) -> Value {
+    let user = payload["user"].take();
+    let object = payload.as_object_mut().unwrap();
+    object.remove("user");
+    object.insert("guild_id".into(), guild.to_string().into());
+    object.insert(
+        "member".into(),
+        json!({
+            "user": user,
+            "roles": [],
+            "joined_at": "2024-01-01T00:00:00.000000+00:00",
+            "deaf": false,
+            "mute": false,
+            "flags": 0,
+            "permissions": "0",
+        }),
+    );
+    payload
+}
+
+/// Builds the headers that Discord attaches to every interaction request.
+pub fn signature_headers(timestamp: &str, signature: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(HeaderName::from_static("x-signature-ed25519"), HeaderValue::from_str(signature).unwrap());
+    headers.insert(HeaderName::from_static("x-signature-timestamp"), HeaderValue::from_str(timestamp).unwrap());
+    headers
+}
+
+fn user_payload(user: u64) -> Value {
+    json!({
+        "id": user.to_string(),
+        "username": format!("user{user}"),
+        "discriminator": "0000",
+        "avatar": null,
+    })
+}
+
+/// Current UNIX timestamp in seconds, as sent in `X-Signature-Timestamp`.
+pub fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
@@ -0,0 +1,900 @@
+use api::{AppBuilder, Ratelimiter};
+use core::{convert::Infallible, time::Duration};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::{Bytes, Frame};
+use hyper::{HeaderMap, Method, StatusCode};
+use quizzo_harness::{in_guild, now, signature_headers, Harness, MockDiscord, APP_ID};
+use serde_json::{json, Value};
+
+fn content(reply: &Value) -> &str {
+    reply["data"]["content"].as_str().unwrap()
+}
+
+#[tokio::test]
+async fn ping_is_answered_with_pong() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+    let reply = harness.interact(&harness.ping()).await;
+    assert_eq!(reply, json!({ "type": 1 }));
+}
+
+#[tokio::test]
+async fn unsigned_requests_are_rejected() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+    let body = serde_json::to_vec(&harness.ping()).unwrap();
+
+    let response = harness.request(Method::POST, "/discord", HeaderMap::new(), body.clone()).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = harness.post("0", "not hex", body.clone()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Signature over a different timestamp
+    let signature = harness.sign("0", &body);
+    let response = harness.post("1", &signature, body).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn replayed_requests_are_rejected() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+    let ping = harness.ping();
+
+    let response = harness.send(&ping).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = harness.send(&ping).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn stale_requests_are_rejected() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    for timestamp in [now() - 3600, now() + 3600] {
+        let body = serde_json::to_vec(&harness.ping()).unwrap();
+        let timestamp = timestamp.to_string();
+        let signature = harness.sign(&timestamp, &body);
+        let response = harness.post(&timestamp, &signature, body).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let body = serde_json::to_vec(&harness.ping()).unwrap();
+    let signature = harness.sign("yesterday", &body);
+    let response = harness.post("yesterday", &signature, body).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn oversized_bodies_are_rejected() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::with(&discord, |builder| builder.max_body_size(1024));
+    let signature = "00".repeat(64);
+    let timestamp = now().to_string();
+
+    // Announced through the size hint
+    let response = harness.post(&timestamp, &signature, vec![b' '; 2048]).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Discovered while streaming
+    let frames = (0..4).map(|_| Ok::<_, Infallible>(Frame::data(Bytes::from(vec![b' '; 512]))));
+    let body = StreamBody::new(futures_util::stream::iter(frames));
+    let headers = signature_headers(&timestamp, &signature);
+    let response = harness.request_body(Method::POST, "/discord", headers, body).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Small enough payloads still go through
+    let reply = harness.interact(&harness.ping()).await;
+    assert_eq!(reply, json!({ "type": 1 }));
+}
+
+#[tokio::test]
+async fn slow_bodies_time_out() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::with(&discord, |builder| builder.read_timeout(Duration::from_millis(100)));
+    let body = StreamBody::new(futures_util::stream::pending::<Result<Frame<Bytes>, Infallible>>());
+    let headers = signature_headers(&now().to_string(), &"00".repeat(64));
+    let response = harness.request_body(Method::POST, "/discord", headers, body).await;
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+}
+
+#[tokio::test]
+async fn unknown_routes_are_not_found() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let response = harness.request(Method::GET, "/health", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = harness.request(Method::GET, "/missing", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = harness.request(Method::DELETE, "/discord", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn health_checks_report_components() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let response = harness.request(Method::GET, "/health/live", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({ "status": "ok" }));
+
+    let response = harness.request(Method::GET, "/health/ready", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["Content-Type"], "application/json");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let report: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(report["status"], "ready");
+    assert_eq!(report["components"]["database"]["status"], "up");
+    assert_eq!(report["components"]["sessions"], json!({ "status": "up", "active": 0 }));
+}
+
+#[tokio::test]
+async fn quiz_errors_are_reported_ephemerally() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let add = harness.command(
+        1,
+        "add",
+        json!([{ "name": "quiz", "type": 4, "value": 1 }, { "name": "choice", "type": 3, "value": "Venus" }]),
+    );
+    let reply = harness.interact(&add).await;
+    assert_eq!(content(&reply), "Resource not found.");
+    assert_eq!(reply["data"]["flags"], 64);
+
+    let create = harness.command(1, "create", json!([{ "name": "question", "type": 3, "value": "" }]));
+    let reply = harness.interact(&create).await;
+    assert_eq!(content(&reply), "Questions must have between 1 and 100 characters.");
+
+    let list = harness.command(1, "list", json!([]));
+    let reply = harness.interact(&list).await;
+    assert_eq!(content(&reply), "You currently have no quizzes registered.");
+
+    // Violated constraints come with their limits. The rejected quiz above still used up the first ID.
+    let create = harness.command(1, "create", json!([{ "name": "question", "type": 3, "value": "Closest star?" }]));
+    assert_eq!(content(&harness.interact(&create).await), "New quiz added: `2`.");
+    let add = harness.command(
+        1,
+        "add",
+        json!([{ "name": "quiz", "type": 4, "value": 2 }, { "name": "choice", "type": 3, "value": "Sun" }]),
+    );
+    harness.interact(&add).await;
+    let edit = harness.command(
+        1,
+        "edit",
+        json!([{
+            "name": "answer",
+            "type": 1,
+            "options": [{ "name": "quiz", "type": 4, "value": 2 }, { "name": "answer", "type": 4, "value": 3 }],
+        }]),
+    );
+    let reply = harness.interact(&edit).await;
+    assert_eq!(content(&reply), "The answer must be between 0 and 0 since this quiz has 1 choice.");
+    let remove = harness.command(
+        1,
+        "remove",
+        json!([{ "name": "quiz", "type": 4, "value": 2 }, { "name": "index", "type": 4, "value": 2 }]),
+    );
+    let reply = harness.interact(&remove).await;
+    assert_eq!(content(&reply), "The index must be between 0 and 0 since this quiz has 1 choice.");
+}
+
+#[tokio::test]
+async fn replies_follow_the_interaction_locale() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    // The client language of the user comes first
+    let mut create = harness.command(1, "create", json!([{ "name": "question", "type": 3, "value": "¿Qué planeta?" }]));
+    create["locale"] = "es-ES".into();
+    assert_eq!(content(&harness.interact(&create).await), "Nuevo quiz agregado: `1`.");
+
+    // Unsupported languages fall back to the guild's, then to English
+    let mut list = in_guild(harness.command(2, "list", json!([])), 10);
+    list["locale"] = "fr".into();
+    list["guild_locale"] = "es-419".into();
+    assert_eq!(content(&harness.interact(&list).await), "Actualmente no tienes quizzes registrados.");
+    list["guild_locale"] = "ja".into();
+    list["id"] = "999999".into();
+    assert_eq!(content(&harness.interact(&list).await), "You currently have no quizzes registered.");
+
+    // Quizzes are announced in the language of the guild
+    let add = harness.command(
+        1,
+        "add",
+        json!([{ "name": "quiz", "type": 4, "value": 1 }, { "name": "choice", "type": 3, "value": "Venus" }]),
+    );
+    harness.interact(&add).await;
+    let edit = harness.command(
+        1,
+        "edit",
+        json!([{
+            "name": "answer",
+            "type": 1,
+            "options": [{ "name": "quiz", "type": 4, "value": 1 }, { "name": "answer", "type": 4, "value": 0 }],
+        }]),
+    );
+    harness.interact(&edit).await;
+    let mut start = in_guild(harness.command(1, "start", json!([{ "name": "quiz", "type": 4, "value": 1 }])), 10);
+    start["locale"] = "en-US".into();
+    start["guild_locale"] = "es-ES".into();
+    let reply = harness.interact(&start).await;
+    assert!(content(&reply).starts_with("**[Expira <t:"));
+    assert_eq!(reply["data"]["components"][0]["components"][0]["placeholder"], "Tu respuesta");
+}
+
+#[tokio::test]
+async fn commands_are_rate_limited() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::with(&discord, |builder| {
+        builder
+            .user_rate_limits("create=2/60, answer=1/60".parse().unwrap())
+            .guild_rate_limits("*=3/60".parse().unwrap())
+    });
+    let create = |user| harness.command(user, "create", json!([{ "name": "question", "type": 3, "value": "Spam?" }]));
+
+    for _ in 0..2 {
+        let reply = harness.interact(&create(1)).await;
+        assert!(content(&reply).starts_with("New quiz added"));
+    }
+    let reply = harness.interact(&create(1)).await;
+    assert_eq!(content(&reply), "Slow down! Please wait 30 seconds before trying again.");
+    assert_eq!(reply["data"]["flags"], 64);
+
+    // Other commands and users have their own limits
+    let reply = harness.interact(&harness.command(1, "list", json!([]))).await;
+    assert_eq!(reply["data"]["embeds"].as_array().unwrap().len(), 2);
+    let reply = harness.interact(&create(2)).await;
+    assert!(content(&reply).starts_with("New quiz added"));
+
+    // Answers count separately, and the limited user cannot change their mind
+    let (_, custom_id) = start_quiz(&harness, 3, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    let reply = harness.interact(&harness.select(4, &custom_id, "0")).await;
+    assert!(content(&reply).starts_with("Your answer **Mercury** has been recorded."));
+    let reply = harness.interact(&harness.select(4, &custom_id, "1")).await;
+    assert_eq!(content(&reply), "Slow down! Please wait 60 seconds before trying again.");
+
+    // Members share the limits of their guild
+    for user in [5, 6, 7] {
+        let reply = harness.interact(&in_guild(create(user), 900)).await;
+        assert!(content(&reply).starts_with("New quiz added"));
+    }
+    let reply = harness.interact(&in_guild(create(8), 900)).await;
+    assert_eq!(content(&reply), "Slow down! Please wait 20 seconds before trying again.");
+
+    let response = harness.request(Method::GET, "/metrics", HeaderMap::new(), Vec::new()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(core::str::from_utf8(&body).unwrap().contains("quizzo_errors_total{variant=\"RateLimited\"} 3\n"));
+}
+
+#[tokio::test]
+async fn quiz_quotas_are_enforced() {
+    let discord = MockDiscord::start().await.unwrap();
+    let quota = db::Quota { per_user: Some(2), per_guild: Some(3) };
+    let harness = Harness::with_store(&discord, db::memory::InMemory::default().with_quota(quota), |builder| builder);
+    let create = |user| harness.command(user, "create", json!([{ "name": "question", "type": 3, "value": "Hoard?" }]));
+
+    for _ in 0..2 {
+        let reply = harness.interact(&in_guild(create(1), 900)).await;
+        assert!(content(&reply).starts_with("New quiz added"));
+    }
+    let reply = harness.interact(&create(1)).await;
+    assert_eq!(content(&reply), "You already have 2 quizzes, which is the limit. Start one of them first.");
+    assert_eq!(reply["data"]["flags"], 64);
+
+    // Quizzes created elsewhere do not count towards the guild
+    harness.interact(&create(2)).await;
+    harness.interact(&in_guild(create(2), 900)).await;
+    let reply = harness.interact(&in_guild(create(3), 900)).await;
+    assert_eq!(content(&reply), "This server already has 3 quizzes, which is the limit. Start one of them first.");
+
+    let key = harness.app().issue_api_key(core::num::NonZeroU64::new(1).unwrap()).await.unwrap();
+    let question = json!({ "question": "Over the API?" });
+    let (status, body) = harness.rest(Method::POST, "/api/v1/quizzes", Some(key.as_str()), Some(question)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"], "quota of 2 stored quizzes reached, start one first");
+}
+
+#[tokio::test]
+async fn metrics_are_exposed() {
+    let discord = MockDiscord::start().await.unwrap();
+    let config =
+        api::OAuthConfig { client_secret: "secret".into(), redirect_uri: "http://quizzo.test/oauth/callback".into() };
+    let harness = Harness::with(&discord, |builder| builder.oauth(config));
+
+    harness.interact(&harness.ping()).await;
+    harness.interact(&harness.command(1, "create", json!([{ "name": "question", "type": 3, "value": "" }]))).await;
+
+    let body = serde_json::to_vec(&harness.ping()).unwrap();
+    let signature = harness.sign("0", &body);
+    let response = harness.post("1", &signature, body).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = harness.request(Method::GET, "/oauth/login", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+
+    let response = harness.request(Method::GET, "/metrics", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["Content-Type"], "text/plain; version=0.0.4");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = core::str::from_utf8(&body).unwrap();
+    assert!(text.contains("quizzo_interactions_total{type=\"ping\",command=\"\"} 1\n"));
+    assert!(text.contains("quizzo_interactions_total{type=\"application_command\",command=\"create\"} 1\n"));
+    assert!(text.contains("quizzo_rest_requests_total{endpoint=\"oauth_login\"} 1\n"));
+    assert!(!text.contains("type=\"oauth\""));
+    assert!(text.contains("quizzo_errors_total{variant=\"BadInput\"} 1\n"));
+    assert!(text.contains("quizzo_signature_failures_total 1\n"));
+    assert!(text.contains("quizzo_db_query_duration_seconds_count{method=\"init_quiz\"} 1\n"));
+    assert!(text.contains("quizzo_active_sessions 0\n"));
+    assert!(text.contains("quizzo_followup_failures_total 0\n"));
+}
+
+#[tokio::test]
+async fn full_quiz_flow_announces_winners() {
+    const AUTHOR: u64 = 100;
+    const WINNER: u64 = 200;
+    const LOSER: u64 = 300;
+
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    // Create the quiz
+    let create = harness.command(
+        AUTHOR,
+        "create",
+        json!([{ "name": "question", "type": 3, "value": "Which planet is the hottest?" }]),
+    );
+    let reply = harness.interact(&create).await;
+    let qid: i64 = content(&reply).trim_start_matches("New quiz added: `").trim_end_matches("`.").parse().unwrap();
+
+    // Add the choices
+    for choice in ["Mercury", "Venus", "Earth"] {
+        let add = harness.command(
+            AUTHOR,
+            "add",
+            json!([{ "name": "quiz", "type": 4, "value": qid }, { "name": "choice", "type": 3, "value": choice }]),
+        );
+        let reply = harness.interact(&add).await;
+        assert_eq!(content(&reply), format!("Successfully added new choice to quiz **[{qid}]**."));
+    }
+
+    // Set the answer
+    let edit = harness.command(
+        AUTHOR,
+        "edit",
+        json!([{
+            "name": "answer",
+            "type": 1,
+            "options": [{ "name": "quiz", "type": 4, "value": qid }, { "name": "answer", "type": 4, "value": 1 }],
+        }]),
+    );
+    let reply = harness.interact(&edit).await;
+    assert_eq!(content(&reply), "The answer property has been edited.");
+
+    // Start the quiz
+    let start = harness.command(AUTHOR, "start", json!([{ "name": "quiz", "type": 4, "value": qid }]));
+    let token = start["token"].as_str().unwrap().to_owned();
+    let reply = harness.interact(&start).await;
+    assert!(content(&reply).ends_with("Which planet is the hottest?"));
+    let menu = &reply["data"]["components"][0]["components"][0];
+    let custom_id = menu["custom_id"].as_str().unwrap();
+    assert_eq!(menu["options"].as_array().unwrap().len(), 3);
+    let view = reply["data"]["components"][1]["components"][0]["custom_id"].as_str().unwrap().to_owned();
+
+    // The quiz is no longer listed
+    let list = harness.command(AUTHOR, "list", json!([]));
+    let reply = harness.interact(&list).await;
+    assert_eq!(content(&reply), "You currently have no quizzes registered.");
+
+    // Answer the quiz, where the loser changes their mind
+    let reply = harness.interact(&harness.click(WINNER, &view)).await;
+    assert!(content(&reply).starts_with("You have not answered yet. The quiz ends <t:"));
+    for (user, choice, label) in
+        [(WINNER, "0", "Mercury"), (LOSER, "1", "Venus"), (WINNER, "1", "Venus"), (LOSER, "2", "Earth")]
+    {
+        let reply = harness.interact(&harness.select(user, custom_id, choice)).await;
+        assert!(content(&reply).starts_with(&format!("Your answer **{label}** has been recorded. The quiz ends <t:")));
+    }
+    let reply = harness.interact(&harness.click(WINNER, &view)).await;
+    assert!(content(&reply).starts_with("Your answer is **Venus**."));
+    let reply = harness.interact(&harness.select(LOSER, custom_id, "3")).await;
+    assert_eq!(content(&reply), "Discord provided an unexpected interaction schema.");
+
+    // Wait for the results to be announced
+    let request = discord.next_request(Duration::from_secs(15)).await.expect("no follow-up sent");
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.path, format!("/webhooks/{APP_ID}/{token}"));
+    let announcement = request.body["content"].as_str().unwrap();
+    assert_eq!(announcement, format!("The correct answer is: ||Venus||. Congratulations to <@{WINNER}>!"));
+
+    // Late answers are rejected
+    let reply = harness.interact(&harness.select(LOSER, custom_id, "1")).await;
+    assert_eq!(content(&reply), "Resource not found.");
+}
+
+/// Creates and starts a quiz as `author`. Returns the interaction token and the select menu's custom ID.
+async fn start_quiz(harness: &Harness, author: u64, question: &str, choices: &[&str], answer: u16) -> (String, String) {
+    start_quiz_in(harness, None, author, question, choices, answer).await
+}
+
+/// Like [`start_quiz`], but optionally starts the quiz in a `guild`.
+async fn start_quiz_in(
+    harness: &Harness,
+    guild: Option<u64>,
+    author: u64,
+    question: &str,
+    choices: &[&str],
+    answer: u16,
+) -> (String, String) {
+    let create = harness.command(author, "create", json!([{ "name": "question", "type": 3, "value": question }]));
+    let reply = harness.interact(&create).await;
+    let qid: i64 = content(&reply).trim_start_matches("New quiz added: `").trim_end_matches("`.").parse().unwrap();
+
+    for choice in choices {
+        let add = harness.command(
+            author,
+            "add",
+            json!([{ "name": "quiz", "type": 4, "value": qid }, { "name": "choice", "type": 3, "value": choice }]),
+        );
+        harness.interact(&add).await;
+    }
+
+    let edit = harness.command(
+        author,
+        "edit",
+        json!([{
+            "name": "answer",
+            "type": 1,
+            "options": [{ "name": "quiz", "type": 4, "value": qid }, { "name": "answer", "type": 4, "value": answer }],
+        }]),
+    );
+    harness.interact(&edit).await;
+
+    let mut start = harness.command(author, "start", json!([{ "name": "quiz", "type": 4, "value": qid }]));
+    if let Some(guild) = guild {
+        start = in_guild(start, guild);
+    }
+    let token = start["token"].as_str().unwrap().to_owned();
+    let reply = harness.interact(&start).await;
+    let custom_id = reply["data"]["components"][0]["components"][0]["custom_id"].as_str().unwrap().to_owned();
+    (token, custom_id)
+}
+
+#[tokio::test]
+async fn guild_settings_shape_quizzes() {
+    const GUILD: u64 = 700;
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+    let settings = |name: &str, options: Value| {
+        let mut payload =
+            in_guild(harness.command(1, "settings", json!([{ "name": name, "type": 1, "options": options }])), GUILD);
+        payload["member"]["permissions"] = "32".into();
+        payload
+    };
+
+    // Only members who can manage the guild may change its settings
+    let show = harness.command(1, "settings", json!([{ "name": "show", "type": 1, "options": [] }]));
+    assert_eq!(content(&harness.interact(&show).await), "This command can only be used in a server.");
+    let mut show = settings("show", json!([]));
+    show["member"]["permissions"] = "0".into();
+    assert_eq!(content(&harness.interact(&show).await), "You need the Manage Server permission to do that.");
+
+    let color = settings("color", json!([{ "name": "color", "type": 3, "value": "teal" }]));
+    let reply = harness.interact(&color).await;
+    assert_eq!(content(&reply), "Colors must be hex codes between #000000 and #FFFFFF, such as #236EA5.");
+
+    for payload in [
+        settings("color", json!([{ "name": "color", "type": 3, "value": "#FF0000" }])),
+        settings("answer-changes", json!([{ "name": "limit", "type": 4, "value": 0 }])),
+        settings("ping-winners", json!([{ "name": "enabled", "type": 5, "value": false }])),
+        settings("allow-channel", json!([{ "name": "channel", "type": 7, "value": "5" }])),
+    ] {
+        harness.interact(&payload).await;
+    }
+    let reply =
+        harness.interact(&settings("expiration", json!([{ "name": "expiration", "type": 4, "value": 10 }]))).await;
+    let embed = &reply["data"]["embeds"][0];
+    assert_eq!(reply["data"]["flags"], 64);
+    assert_eq!(embed["color"], 0xFF0000);
+    let values: Vec<_> = embed["fields"].as_array().unwrap().iter().map(|field| field["value"].clone()).collect();
+    assert_eq!(values, ["10 seconds", "#FF0000", "No", "First answer is final", "<#5>"]);
+
+    // Quizzes only start in the allowed channels, and the rejected quiz stays listed
+    let create =
+        in_guild(harness.command(1, "create", json!([{ "name": "question", "type": 3, "value": "Hottest?" }])), GUILD);
+    assert_eq!(content(&harness.interact(&create).await), "New quiz added: `1`.");
+    let list = in_guild(harness.command(1, "list", json!([])), GUILD);
+    assert_eq!(harness.interact(&list).await["data"]["embeds"][0]["color"], 0xFF0000);
+    for choice in ["Mercury", "Venus"] {
+        let add = harness.command(
+            1,
+            "add",
+            json!([{ "name": "quiz", "type": 4, "value": 1 }, { "name": "choice", "type": 3, "value": choice }]),
+        );
+        harness.interact(&add).await;
+    }
+    let edit = harness.command(
+        1,
+        "edit",
+        json!([{
+            "name": "answer",
+            "type": 1,
+            "options": [{ "name": "quiz", "type": 4, "value": 1 }, { "name": "answer", "type": 4, "value": 1 }],
+        }]),
+    );
+    harness.interact(&edit).await;
+    let mut start = in_guild(harness.command(1, "start", json!([{ "name": "quiz", "type": 4, "value": 1 }])), GUILD);
+    let reply = harness.interact(&start).await;
+    assert_eq!(content(&reply), "Quizzes in this server can only be started in <#5>.");
+
+    // Answers can only be changed once, and winners are not pinged
+    harness.interact(&settings("allow-channel", json!([{ "name": "channel", "type": 7, "value": "1" }]))).await;
+    harness.interact(&settings("answer-changes", json!([{ "name": "limit", "type": 4, "value": 1 }]))).await;
+    start["id"] = harness.next_id().to_string().into();
+    let reply = harness.interact(&start).await;
+    assert!(content(&reply).starts_with("**[Expires <t:"));
+    let custom_id = reply["data"]["components"][0]["components"][0]["custom_id"].as_str().unwrap();
+    for (user, choice, ack) in [
+        (200, "0", "Your answer **Mercury** has been recorded."),
+        (300, "1", "Your answer **Venus** has been recorded."),
+        (200, "1", "Your answer **Venus** has been recorded."),
+        (200, "1", "Your answer **Venus** has been recorded."),
+        (200, "0", "You may only change your answer 1 time, so it remains **Venus**."),
+    ] {
+        let reply = harness.interact(&in_guild(harness.select(user, custom_id, choice), GUILD)).await;
+        assert!(content(&reply).starts_with(ack));
+    }
+    let request = discord.next_request(Duration::from_secs(15)).await.expect("no follow-up sent");
+    assert_eq!(request.body["content"], "The correct answer is: ||Venus||. Congratulations to <@200> <@300>!");
+    assert_eq!(request.body["allowed_mentions"]["parse"], json!([]));
+}
+
+#[tokio::test]
+async fn shutdown_ends_running_quizzes_early() {
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let (token, custom_id) = start_quiz(&harness, 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    let reply = harness.interact(&harness.select(200, &custom_id, "1")).await;
+    assert!(content(&reply).starts_with("Your answer **Venus** has been recorded."));
+
+    // The default expiration of 10 seconds outlasts the timeout below
+    tokio::time::timeout(Duration::from_secs(5), harness.app().shutdown()).await.expect("quizzes were not drained");
+
+    let request = discord.next_request(Duration::from_secs(1)).await.expect("no follow-up sent");
+    assert_eq!(request.path, format!("/webhooks/{APP_ID}/{token}"));
+    let announcement = request.body["content"].as_str().unwrap();
+    assert_eq!(
+        announcement,
+        "This quiz ended early because the bot is shutting down. The correct answer is: ||Venus||. Congratulations to <@200>!"
+    );
+
+    let reply = harness.interact(&harness.select(300, &custom_id, "1")).await;
+    assert_eq!(content(&reply), "Resource not found.");
+}
+
+#[tokio::test]
+async fn followups_are_retried_on_outages() {
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let (token, _) = start_quiz(&harness, 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    discord.respond_with(StatusCode::SERVICE_UNAVAILABLE);
+    harness.app().shutdown().await;
+
+    for _ in 0..2 {
+        let request = discord.next_request(Duration::from_secs(1)).await.expect("no follow-up sent");
+        assert_eq!(request.path, format!("/webhooks/{APP_ID}/{token}"));
+    }
+    assert!(discord.next_request(Duration::from_millis(100)).await.is_none());
+}
+
+#[tokio::test]
+async fn failed_followups_fall_back_to_channel_messages() {
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    let (token, _) = start_quiz(&harness, 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    discord.respond_with(StatusCode::NOT_FOUND);
+    harness.app().shutdown().await;
+
+    // Client errors such as an expired token are not retried
+    let request = discord.next_request(Duration::from_secs(1)).await.expect("no follow-up sent");
+    assert_eq!(request.path, format!("/webhooks/{APP_ID}/{token}"));
+    let request = discord.next_request(Duration::from_secs(1)).await.expect("no fallback sent");
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.path, "/channels/1/messages");
+    assert!(request.body["content"].as_str().unwrap().ends_with("Nobody got it right..."));
+
+    let response = harness.request(Method::GET, "/metrics", HeaderMap::new(), Vec::new()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(core::str::from_utf8(&body).unwrap().contains("quizzo_followup_failures_total 0\n"));
+}
+
+#[tokio::test]
+async fn undelivered_results_are_recorded() {
+    let mut discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+
+    start_quiz(&harness, 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    discord.respond_with(StatusCode::NOT_FOUND);
+    discord.respond_with(StatusCode::FORBIDDEN);
+    harness.app().shutdown().await;
+
+    assert!(discord.next_request(Duration::from_secs(1)).await.is_some());
+    assert!(discord.next_request(Duration::from_secs(1)).await.is_some());
+    let response = harness.request(Method::GET, "/metrics", HeaderMap::new(), Vec::new()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(core::str::from_utf8(&body).unwrap().contains("quizzo_followup_failures_total 1\n"));
+}
+
+#[tokio::test]
+async fn quiz_results_are_posted_to_webhooks() {
+    const GUILD: u64 = 900;
+    let mut discord = MockDiscord::start().await.unwrap();
+    let mut receiver = MockDiscord::start().await.unwrap();
+    let harness =
+        Harness::with(&discord, |builder| builder.webhook_attempts(3).webhook_backoff(Duration::from_millis(10)));
+    let guild = core::num::NonZeroU64::new(GUILD).unwrap();
+    let url = format!("http://{}/quizzo", receiver.url_host());
+    let secret = harness.app().add_webhook(guild, &url).await.unwrap();
+
+    let (_, custom_id) =
+        start_quiz_in(&harness, Some(GUILD), 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    for (user, choice) in [(200, "0"), (300, "1"), (200, "1")] {
+        let select = in_guild(harness.select(user, &custom_id, choice), GUILD);
+        let reply = harness.interact(&select).await;
+        assert!(content(&reply).starts_with("Your answer **"));
+    }
+
+    // The first delivery fails, so the payload arrives with the second attempt after the announcement
+    receiver.respond_with(StatusCode::SERVICE_UNAVAILABLE);
+    discord.next_request(Duration::from_secs(15)).await.expect("no follow-up sent");
+    let first = receiver.next_request(Duration::from_secs(1)).await.expect("no webhook sent");
+    let request = receiver.next_request(Duration::from_secs(1)).await.expect("webhook not retried");
+    assert_eq!(first.raw, request.raw);
+    assert_eq!((request.method, request.path.as_str()), (Method::POST, "/quizzo"));
+
+    let timestamp: u64 = request.headers["x-quizzo-timestamp"].to_str().unwrap().parse().unwrap();
+    assert!(timestamp.abs_diff(now()) < 60);
+    assert_eq!(request.headers["x-quizzo-signature"], api::sign_webhook(&secret, timestamp, &request.raw));
+
+    let payload = request.body;
+    assert_eq!(payload["event"], "quiz.finished");
+    assert_eq!(payload["guild_id"], GUILD.to_string());
+    assert_eq!(payload["session_id"], custom_id);
+    assert_eq!(payload["author_id"], "100");
+    assert_eq!(payload["question"], "Which planet is the hottest?");
+    assert_eq!(payload["choices"], json!(["Mercury", "Venus"]));
+    assert_eq!(payload["answer"], 1);
+    assert_eq!(payload["ended_early"], false);
+    let responses = payload["responses"].as_array().unwrap();
+    assert_eq!(responses.len(), 2);
+    for (response, (user, choice)) in responses.iter().zip([("200", 1), ("300", 1)]) {
+        assert_eq!(response["user_id"], user);
+        assert_eq!(response["choice"], choice);
+        assert_eq!(response["correct"], true);
+        assert!(response["elapsed_ms"].as_u64().unwrap() < 10_000);
+    }
+    assert!(receiver.next_request(Duration::from_millis(100)).await.is_none());
+    assert!(harness.app().dead_letters(guild).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn undeliverable_webhooks_are_dead_lettered() {
+    const GUILD: u64 = 900;
+    let discord = MockDiscord::start().await.unwrap();
+    let mut receiver = MockDiscord::start().await.unwrap();
+    let harness =
+        Harness::with(&discord, |builder| builder.webhook_attempts(3).webhook_backoff(Duration::from_millis(10)));
+    let guild = core::num::NonZeroU64::new(GUILD).unwrap();
+    let url = format!("http://{}/quizzo", receiver.url_host());
+    harness.app().add_webhook(guild, &url).await.unwrap();
+
+    // Quizzes outside of the guild are not reported
+    start_quiz(&harness, 100, "Is this a DM?", &["Yes", "No"], 0).await;
+    start_quiz_in(&harness, Some(GUILD), 100, "Which planet is the hottest?", &["Mercury", "Venus"], 1).await;
+    receiver.respond_with(StatusCode::INTERNAL_SERVER_ERROR);
+    harness.app().shutdown().await;
+
+    // Shutting down leaves no time for retries
+    let request = receiver.next_request(Duration::from_secs(1)).await.expect("no webhook sent");
+    assert_eq!(request.body["question"], "Which planet is the hottest?");
+    assert_eq!(request.body["ended_early"], true);
+    assert!(receiver.next_request(Duration::from_millis(100)).await.is_none());
+
+    let letters = harness.app().dead_letters(guild).await.unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].url, url);
+    assert_eq!(letters[0].attempts, 1);
+    assert!(letters[0].error.starts_with("status 500"));
+    assert_eq!(letters[0].payload.as_bytes(), request.raw);
+
+    let response = harness.request(Method::GET, "/metrics", HeaderMap::new(), Vec::new()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(core::str::from_utf8(&body).unwrap().contains("quizzo_webhook_failures_total 1\n"));
+}
+
+#[tokio::test]
+async fn register_puts_every_command() {
+    let mut discord = MockDiscord::start().await.unwrap();
+    let id = core::num::NonZeroU64::new(APP_ID).unwrap();
+    let guild = core::num::NonZeroU64::new(42).unwrap();
+    let url = discord.url();
+    let builder = || AppBuilder::new(id, "mock".into()).api_url(url.clone()).ratelimiter(Ratelimiter::Disabled);
+
+    builder().register(None).await.unwrap();
+    let request = discord.next_request(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.path, format!("/applications/{APP_ID}/commands"));
+    let names: Vec<_> = request.body.as_array().unwrap().iter().map(|cmd| cmd["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["create", "list", "start", "add", "remove", "edit", "help", "about", "settings"]);
+    assert_eq!(request.body[0]["name_localizations"]["es-ES"], "crear");
+    assert_eq!(request.body[8]["default_member_permissions"], "32");
+    assert_eq!(request.body[5]["options"][2]["name_localizations"]["es-419"], "duración");
+
+    builder().register(Some(guild)).await.unwrap();
+    let request = discord.next_request(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.path, format!("/applications/{APP_ID}/guilds/{guild}/commands"));
+}
+
+#[tokio::test]
+async fn rest_api_manages_quizzes() {
+    let discord = MockDiscord::start().await.unwrap();
+    let harness = Harness::new(&discord);
+    let author = core::num::NonZeroU64::new(100).unwrap();
+    let key = harness.app().issue_api_key(author).await.unwrap();
+    let key = Some(key.as_str());
+
+    let (status, body) = harness.rest(Method::GET, "/api/v1/quizzes", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "missing bearer API key");
+    let (status, _) = harness.rest(Method::GET, "/api/v1/quizzes", Some("quizzo_forged"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let question = json!({ "question": "Which planet is the hottest?" });
+    let (status, quiz) = harness.rest(Method::POST, "/api/v1/quizzes", key, Some(question)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(quiz["expiration"], 10);
+    let id = quiz["id"].as_i64().unwrap();
+
+    for choice in ["Mercury", "Venus", "Earth"] {
+        let path = format!("/api/v1/quizzes/{id}/choices");
+        let (status, _) = harness.rest(Method::POST, &path, key, Some(json!({ "choice": choice }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let path = format!("/api/v1/quizzes/{id}");
+    let (_, before) = harness.rest(Method::GET, &path, key, None).await;
+    let (status, body) = harness.rest(Method::PATCH, &path, key, Some(json!({ "answer": 7 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "answer must be between 0 and 2");
+
+    // A rejected field keeps the valid ones from being saved
+    let patch = json!({ "question": "Which planet is the coldest?", "expiration": 20, "answer": 7 });
+    let (status, _) = harness.rest(Method::PATCH, &path, key, Some(patch)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, after) = harness.rest(Method::GET, &path, key, None).await;
+    assert_eq!(after, before);
+    let (status, body) = harness.rest(Method::PATCH, &path, key, Some(json!({ "colour": "red" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().starts_with("invalid body"));
+
+    let (status, body) = harness.rest(Method::DELETE, &format!("/api/v1/quizzes/{id}/choices/2"), key, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["choice"], "Earth");
+
+    let patch = json!({ "answer": 1, "expiration": 30 });
+    let (status, quiz) = harness.rest(Method::PATCH, &path, key, Some(patch)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        quiz,
+        json!({ "id": id, "question": "Which planet is the hottest?", "choices": ["Mercury", "Venus"], "answer": 1, "expiration": 30 })
+    );
+
+    let (status, body) = harness.rest(Method::GET, "/api/v1/quizzes", key, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["quizzes"], json!([quiz]));
+
+    // Keys are scoped to their owner
+    let other = harness.app().issue_api_key(core::num::NonZeroU64::new(200).unwrap()).await.unwrap();
+    let (status, _) = harness.rest(Method::GET, &path, Some(other.as_str()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = harness.rest(Method::PUT, &path, key, None).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+    // Quizzes written over HTTP are the same ones that Discord users start
+    let start = harness.command(100, "start", json!([{ "name": "quiz", "type": 4, "value": id }]));
+    let reply = harness.interact(&start).await;
+    assert!(reply["data"]["components"][0]["components"][0]["custom_id"].is_string());
+    let (status, _) = harness.rest(Method::GET, &path, key, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Drafts without an answer can still be deleted
+    let question = json!({ "question": "Draft?" });
+    let (_, draft) = harness.rest(Method::POST, "/api/v1/quizzes", key, Some(question)).await;
+    let path = format!("/api/v1/quizzes/{}", draft["id"]);
+    let (status, body) = harness.rest(Method::DELETE, &path, key, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["question"], "Draft?");
+    let (status, _) = harness.rest(Method::DELETE, &path, key, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn oauth_login_authenticates_rest_api() {
+    use hyper::header::{HeaderValue, AUTHORIZATION, COOKIE, LOCATION, SET_COOKIE};
+    let mut discord = MockDiscord::start().await.unwrap();
+    let config =
+        api::OAuthConfig { client_secret: "secret".into(), redirect_uri: "http://quizzo.test/oauth/callback".into() };
+    let harness = Harness::with(&discord, |builder| builder.oauth(config));
+    let set_cookie = |response: &hyper::Response<_>, name: &str| {
+        let prefix = format!("{name}=");
+        response.headers().get_all(SET_COOKIE).iter().find_map(|value| {
+            let value = value.to_str().unwrap().strip_prefix(prefix.as_str())?;
+            Some(value.split(';').next().unwrap().to_owned())
+        })
+    };
+
+    let response = harness.request(Method::GET, "/oauth/login", HeaderMap::new(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let location = response.headers()[LOCATION].to_str().unwrap();
+    assert!(location.starts_with(&format!("http://{}/oauth2/authorize?", discord.url_host())));
+    assert!(location.contains(&format!("client_id={APP_ID}")));
+    assert!(location.contains("redirect_uri=http%3A%2F%2Fquizzo.test%2Foauth%2Fcallback"));
+    let state = set_cookie(&response, "quizzo_oauth_state").unwrap();
+    assert!(location.contains(&format!("state={state}")));
+
+    // A callback that this browser did not start is rejected before anything is sent to Discord
+    let mut headers = HeaderMap::new();
+    headers.insert(COOKIE, HeaderValue::from_str(&format!("quizzo_oauth_state={state}")).unwrap());
+    let path = "/oauth/callback?code=abc&state=forged";
+    let response = harness.request(Method::GET, path, headers.clone(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    discord.respond_json(StatusCode::OK, json!({ "access_token": "token", "token_type": "Bearer" }));
+    discord.respond_json(StatusCode::OK, json!({ "id": "100", "username": "quizmaster" }));
+    let path = format!("/oauth/callback?code=abc&state={state}");
+    let response = harness.request(Method::GET, &path, headers, Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session = set_cookie(&response, "quizzo_session").unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({ "user": "100" }));
+
+    let token = discord.next_request(Duration::from_secs(1)).await.unwrap();
+    assert_eq!((token.method, token.path.as_str()), (Method::POST, "/oauth2/token"));
+    let form = token.body.as_str().unwrap();
+    for param in ["grant_type=authorization_code", "code=abc", "client_secret=secret", &format!("client_id={APP_ID}")] {
+        assert!(form.split('&').any(|pair| pair == param), "missing `{param}` in `{form}`");
+    }
+    let user = discord.next_request(Duration::from_secs(1)).await.unwrap();
+    assert_eq!((user.method, user.path.as_str()), (Method::GET, "/users/@me"));
+    assert_eq!(user.headers[AUTHORIZATION], "Bearer token");
+
+    // The session reaches the same quizzes as the API key of that user
+    let key = harness.app().issue_api_key(core::num::NonZeroU64::new(100).unwrap()).await.unwrap();
+    let question = json!({ "question": "Is this logged in?" });
+    let (status, _) = harness.rest(Method::POST, "/api/v1/quizzes", Some(key.as_str()), Some(question)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(COOKIE, HeaderValue::from_str(&format!("quizzo_session={session}")).unwrap());
+    let response = harness.request(Method::GET, "/api/v1/quizzes", headers.clone(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["quizzes"][0]["question"], "Is this logged in?");
+
+    let response = harness.request(Method::POST, "/oauth/logout", headers.clone(), Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = harness.request(Method::GET, "/api/v1/quizzes", headers, Vec::new()).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -3,9 +3,9 @@ extern crate alloc;
 
 use alloc::{string::String, vec::Vec};
 use core::num::NonZeroI16;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Debug, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct RawQuiz {
     /// Question to be displayed in chat.
     pub question: String,
@@ -17,7 +17,7 @@ pub struct RawQuiz {
     pub expiration: i16,
 }
 
-#[derive(PartialEq, Eq, Debug, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct Quiz {
     /// Monotonically increasing quiz ID.
     pub id: NonZeroI16,
@@ -1,4 +1,4 @@
-CREATE TABLE quiz(
+CREATE TABLE IF NOT EXISTS quiz(
     -- Monotonically increasing ID for each quiz.
     id SMALLSERIAL NOT NULL,
     -- Discord User ID.
@@ -18,3 +18,91 @@ CREATE TABLE quiz(
     expiration SMALLINT NOT NULL DEFAULT 10 CHECK(expiration BETWEEN 10 AND 600),
     PRIMARY KEY (id, author)
 );
+
+-- Discord Guild ID where the quiz was created, if any. Counts towards the quota of that guild.
+ALTER TABLE quiz ADD COLUMN IF NOT EXISTS guild BIGINT CHECK(guild != 0);
+
+-- Stores a new quiz unless its author or guild has reached the quota (where `NULL` lifts the limit), in which case
+-- `id` is `NULL`. The advisory locks (keyed by the Discord ID, which never collides between users and guilds) keep
+-- concurrent connections from counting the same quizzes. Authors are always locked before guilds to avoid deadlocks.
+CREATE OR REPLACE FUNCTION init_quiz(
+    new_author BIGINT,
+    new_guild BIGINT,
+    new_question VARCHAR,
+    new_expiration SMALLINT,
+    per_user BIGINT,
+    per_guild BIGINT,
+    OUT id SMALLINT,
+    OUT by_user BIGINT,
+    OUT by_guild BIGINT
+) LANGUAGE plpgsql AS $$
+BEGIN
+    IF per_user IS NOT NULL THEN
+        PERFORM pg_advisory_xact_lock(new_author);
+    END IF;
+    IF per_guild IS NOT NULL AND new_guild IS NOT NULL THEN
+        PERFORM pg_advisory_xact_lock(new_guild);
+    END IF;
+
+    SELECT COUNT(*) FILTER (WHERE quiz.author = new_author), COUNT(*) FILTER (WHERE quiz.guild = new_guild)
+        INTO by_user, by_guild
+        FROM quiz WHERE quiz.author = new_author OR quiz.guild = new_guild;
+    IF (per_user IS NULL OR by_user < per_user) AND (per_guild IS NULL OR by_guild < per_guild) THEN
+        INSERT INTO quiz (author, guild, question, expiration)
+            VALUES (new_author, new_guild, new_question, new_expiration)
+            RETURNING quiz.id INTO id;
+    END IF;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS api_key(
+    -- Discord User ID. Each user holds at most one key.
+    author BIGINT NOT NULL CHECK(author != 0) PRIMARY KEY,
+    -- SHA-256 digest of the bearer token. The token itself is never stored.
+    digest BYTEA NOT NULL UNIQUE CHECK(LENGTH(digest) = 32)
+);
+
+CREATE TABLE IF NOT EXISTS webhook(
+    -- Discord Guild ID whose finished quizzes are reported.
+    guild BIGINT NOT NULL CHECK(guild != 0),
+    -- Endpoint that receives the results as a signed JSON payload.
+    url VARCHAR(2048) NOT NULL CHECK(url LIKE 'http://%' OR url LIKE 'https://%'),
+    -- Shared secret for the HMAC-SHA256 signature of each payload.
+    secret VARCHAR(128) NOT NULL CHECK(secret != ''),
+    PRIMARY KEY (guild, url)
+);
+
+CREATE TABLE IF NOT EXISTS webhook_dead_letter(
+    id BIGSERIAL NOT NULL PRIMARY KEY,
+    -- Discord Guild ID of the finished quiz.
+    guild BIGINT NOT NULL CHECK(guild != 0),
+    -- Endpoint that failed to accept the payload. Kept even if the webhook is later removed.
+    url VARCHAR(2048) NOT NULL,
+    -- The payload that could not be delivered. The signing timestamp is not kept, so redeliveries must be signed anew.
+    payload JSON NOT NULL,
+    -- Number of delivery attempts made.
+    attempts SMALLINT NOT NULL CHECK(attempts > 0),
+    -- Reason why the last attempt failed.
+    error TEXT NOT NULL,
+    failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS guild_settings(
+    -- Discord Guild ID. Guilds without a row use the defaults below.
+    guild BIGINT NOT NULL CHECK(guild != 0) PRIMARY KEY,
+    -- Number of seconds before quizzes created in the guild expire. `NULL` defers to the configured default.
+    expiration SMALLINT CHECK(expiration BETWEEN 10 AND 600),
+    -- Embed color as `0xRRGGBB`. `NULL` defers to the configured brand color.
+    color INTEGER CHECK(color BETWEEN 0 AND 16777215),
+    -- Whether the results mention (and thus ping) the winners.
+    ping_winners BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Number of times participants may change their answer before the quiz expires. `0` makes the first answer
+    -- final, whereas `NULL` allows any number of changes (i.e., the last answer wins).
+    answer_changes SMALLINT CHECK(answer_changes BETWEEN 0 AND 10),
+    -- Discord Channel IDs where quizzes may be started. Empty allows every channel.
+    channels BIGINT[]
+        NOT NULL
+        DEFAULT '{}'
+        CONSTRAINT guild_settings_channels_length_check
+        CHECK(CARDINALITY(channels) <= 25)
+);
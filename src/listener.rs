@@ -0,0 +1,161 @@
+//! Accepts connections on either a TCP socket or a Unix domain socket.
+
+use core::{
+    fmt::{self, Display},
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
+use std::{io, net::SocketAddr, path::PathBuf};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Where the server listens: an IPv4 or IPv6 socket address, or `unix:/path/to/socket`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddr {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = value.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err("expected a socket path after `unix:`");
+            }
+            return Ok(Self::Unix(path.into()));
+        }
+        value
+            .parse()
+            .map(Self::Tcp)
+            .map_err(|_| "expected an address such as `0.0.0.0:3000`, `[::]:3000` or `unix:/path`")
+    }
+}
+
+impl Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => addr.fmt(f),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+pub enum Listener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+impl Listener {
+    /// Binds to the given address. Must be called within a Tokio runtime.
+    pub fn bind(addr: &ListenAddr) -> io::Result<Self> {
+        match addr {
+            ListenAddr::Tcp(addr) => {
+                let listener = std::net::TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                tokio::net::TcpListener::from_std(listener).map(Self::Tcp)
+            }
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                // A socket left behind by a previous run would otherwise make the bind fail
+                use std::os::unix::fs::FileTypeExt;
+                if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    std::fs::remove_file(path)?;
+                }
+                tokio::net::UnixListener::bind(path).map(|listener| Self::Unix(listener, path.clone()))
+            }
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "Unix sockets are not supported")),
+        }
+    }
+
+    /// The address that was actually bound (e.g., with the port chosen by the OS).
+    pub fn local_addr(&self) -> io::Result<ListenAddr> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr().map(ListenAddr::Tcp),
+            #[cfg(unix)]
+            Self::Unix(_, path) => Ok(ListenAddr::Unix(path.clone())),
+        }
+    }
+
+    pub async fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Self::Tcp(listener) => listener.accept().await.map(|(stream, _)| Stream::Tcp(stream)),
+            #[cfg(unix)]
+            Self::Unix(listener, _) => listener.accept().await.map(|(stream, _)| Stream::Unix(stream)),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            if let Err(err) = std::fs::remove_file(&*path) {
+                tracing::warn!(error = %err, "cannot remove socket at {}", path.display());
+            }
+        }
+    }
+}
+
+/// A connection accepted by a [`Listener`].
+pub enum Stream {
+    Tcp(tokio::net::TcpStream),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListenAddr;
+
+    #[test]
+    fn parse_listen_addr() {
+        let v4: ListenAddr = "0.0.0.0:3000".parse().unwrap();
+        assert_eq!(v4, ListenAddr::Tcp(([0, 0, 0, 0], 3000).into()));
+        let v6: ListenAddr = "[::]:3000".parse().unwrap();
+        assert_eq!(v6, ListenAddr::Tcp((core::net::Ipv6Addr::UNSPECIFIED, 3000).into()));
+        let unix: ListenAddr = "unix:/run/quizzo.sock".parse().unwrap();
+        assert_eq!(unix, ListenAddr::Unix("/run/quizzo.sock".into()));
+        assert_eq!(unix.to_string(), "unix:/run/quizzo.sock");
+        assert!("unix:".parse::<ListenAddr>().is_err());
+        assert!("localhost".parse::<ListenAddr>().is_err());
+    }
+}
@@ -1,87 +1,305 @@
+mod listener;
+mod settings;
+mod tls;
+
+use settings::{Log, LogFormat, Settings};
+
+/// Installs the global subscriber.
+fn init_logging(log: &Log) {
+    use tracing_subscriber::{fmt, EnvFilter};
+    let builder = fmt().with_env_filter(EnvFilter::new(&log.level.0));
+    match log.format {
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).with_span_list(true).init(),
+        LogFormat::Text => builder.init(),
+    }
+}
+
 fn main() -> anyhow::Result<()> {
-    env_logger::init();
-    log::info!("starting up");
-
-    // Retrieve the public key
-    use std::env::var;
-    let pub_key = var("PUB_KEY")?.into_bytes();
-    let mut pub_bytes = [0; 32];
-    hex::decode_to_slice(pub_key, &mut pub_bytes)?;
-    let pub_key = api::VerifyingKey::from_bytes(&pub_bytes)?;
-    log::debug!("loaded public key");
-
-    // Set up Postgres driver configuration
-    let app_port = var("PORT")?.parse()?;
-    let app_id = var("APP_ID")?.parse()?;
-    let bot_token = var("BOT_TOKEN")?;
-    let config = var("PG_URL")?.parse::<api::Config>()?;
-
-    use std::net::{Ipv4Addr, TcpListener};
-    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, app_port))?;
-    listener.set_nonblocking(true)?;
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => serve(),
+        Some("register") => register(args),
+        Some("api-key") => api_key(args),
+        Some("webhook") => webhook(args),
+        Some(other) => anyhow::bail!("unknown subcommand `{other}`"),
+    }
+}
 
-    let addr = listener.local_addr()?;
-    log::info!("listening to {addr}");
+/// Overwrites the slash commands with the ones that the bot handles.
+fn register(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let guild = match (args.next().as_deref(), args.next(), args.next()) {
+        (None, None, None) => None,
+        (Some("--guild"), Some(id), None) => Some(id.parse()?),
+        _ => anyhow::bail!("usage: quizzo register [--guild ID]"),
+    };
+
+    let (log, discord) = Settings::load_discord()?;
+    init_logging(&log);
+    let builder = discord.builder();
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_io().enable_time().build()?;
+    runtime.block_on(builder.register(guild))?;
+
+    match guild {
+        Some(guild) => tracing::info!("registered commands in guild {guild}"),
+        None => tracing::info!("registered global commands"),
+    }
+    Ok(())
+}
+
+/// Issues a REST API key for a Discord user, replacing any previous one, or revokes it with `--revoke`.
+fn api_key(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let (revoke, user) = match (args.next(), args.next(), args.next()) {
+        (Some(flag), Some(user), None) if flag == "--revoke" => (true, user),
+        (Some(user), None, None) if !user.starts_with('-') => (false, user),
+        _ => anyhow::bail!("usage: quizzo api-key [--revoke] USER_ID"),
+    };
+    let user: core::num::NonZeroU64 = user.parse()?;
+
+    with_database(|db| async move {
+        use api::QuizStore;
+        if revoke {
+            db.remove_api_key(user).await.map_err(|err| anyhow::anyhow!("cannot revoke API key: {err:?}"))?;
+            tracing::info!("revoked API key of user {user}");
+        } else {
+            let key = api::ApiKey::generate()?;
+            db.set_api_key(user, key.digest()).await.map_err(|err| anyhow::anyhow!("cannot issue API key: {err:?}"))?;
+            tracing::info!("issued API key for user {user}");
+            println!("{}", key.as_str());
+        }
+        Ok(())
+    })
+}
+
+/// Manages the endpoints that receive the results of quizzes finished in a guild.
+fn webhook(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    const USAGE: &str =
+        "usage: quizzo webhook (add GUILD_ID URL | remove GUILD_ID URL | list GUILD_ID | dead-letters GUILD_ID)";
+    let args = (args.next(), args.next(), args.next(), args.next());
+    let (action, guild, url) = match args {
+        (Some(action), Some(guild), Some(url), None) if action == "add" || action == "remove" => {
+            (action, guild, Some(url))
+        }
+        (Some(action), Some(guild), None, None) if action == "list" || action == "dead-letters" => {
+            (action, guild, None)
+        }
+        _ => anyhow::bail!(USAGE),
+    };
+    let guild: core::num::NonZeroU64 = guild.parse()?;
+
+    with_database(|db| async move {
+        use api::QuizStore;
+        match (action.as_str(), url) {
+            ("add", Some(url)) => {
+                let secret = api::webhook_secret()?;
+                db.set_webhook(guild, &url, &secret)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("cannot add webhook: {err:?}"))?;
+                tracing::info!("added webhook {url} to guild {guild}");
+                println!("{secret}");
+            }
+            ("remove", Some(url)) => {
+                db.remove_webhook(guild, &url)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("cannot remove webhook: {err:?}"))?;
+                tracing::info!("removed webhook {url} from guild {guild}");
+            }
+            ("list", None) => {
+                let hooks =
+                    db.get_webhooks(guild).await.map_err(|err| anyhow::anyhow!("cannot list webhooks: {err:?}"))?;
+                for hook in hooks {
+                    println!("{}", hook.url);
+                }
+            }
+            _ => {
+                let letters = db
+                    .get_dead_letters(guild)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("cannot list dead letters: {err:?}"))?;
+                for api::DeadLetter { url, payload, attempts, error } in letters {
+                    println!("{url}\t{attempts}\t{error}\t{payload}");
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Runs `task` against the configured database. Only the log and database settings are required.
+fn with_database<F, T>(task: F) -> anyhow::Result<()>
+where
+    F: FnOnce(api::Database) -> T,
+    T: core::future::Future<Output = anyhow::Result<()>>,
+{
+    let (log, database) = Settings::load_database()?;
+    init_logging(&log);
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_io().enable_time().build()?;
+    runtime.block_on(async {
+        let (client, connection) = database.config.connect(database.tls).await?;
+        let connection = tokio::spawn(connection);
+
+        // The client is dropped along with the task, which lets the connection wind down
+        task(api::Database::from(client)).await?;
+        connection.await??;
+        anyhow::Ok(())
+    })
+}
+
+/// Resolves once the process is asked to stop via Ctrl+C or `SIGTERM`.
+async fn stop_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => res,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+/// Receives `SIGHUP`, which asks for the TLS certificate to be reloaded.
+struct Hangup(#[cfg(unix)] tokio::signal::unix::Signal);
+
+impl Hangup {
+    fn new() -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            signal(SignalKind::hangup()).map(Self)
+        }
+        #[cfg(not(unix))]
+        Ok(Self())
+    }
+
+    async fn recv(&mut self) -> Option<()> {
+        #[cfg(unix)]
+        {
+            self.0.recv().await
+        }
+        #[cfg(not(unix))]
+        core::future::pending().await
+    }
+}
+
+fn serve() -> anyhow::Result<()> {
+    let settings = Settings::load()?;
+    init_logging(&settings.log);
+    tracing::info!("starting up");
+
+    let builder = settings.builder();
+    let Settings { server, database, quiz, .. } = settings;
+    let pub_key = server.public_key;
+    let grace_period = server.shutdown_grace_period;
+    let handshake_timeout = server.read_timeout.unwrap_or(api::AppBuilder::DEFAULT_READ_TIMEOUT);
+    let tls = server.tls.map(tls::Tls::load).transpose()?;
 
     let runtime = tokio::runtime::Builder::new_multi_thread().enable_io().enable_time().build()?;
-    let tcp = {
+    let listener = {
         let _guard = runtime.enter();
-        tokio::net::TcpListener::from_std(listener)?
+        listener::Listener::bind(&server.bind)?
     };
 
+    let addr = listener.local_addr()?;
+    tracing::info!(%addr, "listening");
+
     runtime.block_on(async {
         let (client, connection) = loop {
             // HACK: Railway Private Networking requires 100ms to set up.
-            let err = match config.connect(api::NoTls).await {
+            let err = match database.config.connect(database.tls.clone()).await {
                 Ok(pair) => break pair,
                 Err(err) => err,
             };
-            log::error!("{err}");
+            tracing::error!(error = %err, "cannot connect to PostgreSQL");
             tokio::time::sleep(core::time::Duration::from_millis(100)).await;
         };
 
         use core::pin::pin;
         let mut postgres = pin!(runtime.spawn(connection));
-        log::info!("PostgreSQL driver connected");
+        tracing::info!("PostgreSQL driver connected");
 
-        let app = api::App::new(client.into(), app_id, bot_token, pub_key);
+        let app = builder.build(api::Database::from(client).with_quota(quiz.quota), pub_key);
         let state = std::sync::Arc::new(app);
 
         let http = hyper::server::conn::http1::Builder::new();
-        let mut stop = pin!(tokio::signal::ctrl_c());
+        let graceful = hyper_util::server::graceful::GracefulShutdown::new();
+        let mut stop = pin!(stop_signal());
+        let mut hangup = tls.as_ref().map(|_| Hangup::new()).transpose()?;
         loop {
             tokio::select! {
-                Ok((stream, _)) = tcp.accept() => {
+                Ok(stream) = listener.accept() => {
                     let outer = state.clone();
                     let service = hyper::service::service_fn(move |req| {
                         let inner = outer.clone();
                         let (hyper::http::request::Parts { method, uri, headers, .. }, body) = req.into_parts();
                         async move {
                             let mut response = Default::default();
-                            inner.try_respond(&mut response, method, uri.path(), headers, body).await;
+                            let target = uri.path_and_query().map_or("/", |target| target.as_str());
+                            inner.try_respond(&mut response, method, target, headers, body).await;
                             Ok::<_, core::convert::Infallible>(response)
                         }
                     });
-                    let io = hyper_util::rt::TokioIo::new(stream);
-                    runtime.spawn(http.serve_connection(io, service));
+                    use hyper_util::rt::TokioIo;
+                    let Some(tls) = &tls else {
+                        runtime.spawn(graceful.watch(http.serve_connection(TokioIo::new(stream), service)));
+                        continue;
+                    };
+
+                    // Handshake off the accept loop, but still let the graceful shutdown wait for it. Clients
+                    // that stall the handshake are cut off like slow bodies are.
+                    let acceptor = tls.acceptor();
+                    let watcher = graceful.watcher();
+                    let http = http.clone();
+                    runtime.spawn(async move {
+                        match tokio::time::timeout(handshake_timeout, acceptor.accept(stream)).await {
+                            Ok(Ok(stream)) => {
+                                watcher.watch(http.serve_connection(TokioIo::new(stream), service)).await
+                            }
+                            Ok(Err(err)) => {
+                                tracing::warn!(error = %err, "TLS handshake failed");
+                                Ok(())
+                            }
+                            Err(_) => {
+                                tracing::warn!(timeout = ?handshake_timeout, "TLS handshake timed out");
+                                Ok(())
+                            }
+                        }
+                    });
+                    continue;
+                }
+                Some(()) = async { hangup.as_mut()?.recv().await } => {
+                    let Some(tls) = &tls else { continue };
+                    match tls.reload() {
+                        Ok(()) => tracing::info!("TLS certificate reloaded"),
+                        Err(err) => tracing::error!(error = format!("{err:#}"), "cannot reload TLS certificate"),
+                    }
                     continue;
                 }
                 stop_res = &mut stop => {
-                    log::info!("stop signal received");
+                    tracing::info!("stop signal received");
                     stop_res?;
                     break;
                 },
                 conn_res = &mut postgres => {
-                    log::info!("PostgreSQL disconnected");
+                    tracing::info!("PostgreSQL disconnected");
                     conn_res??;
                     break;
                 },
                 else => continue,
             }
         }
+
+        // Stop accepting new connections, then let in-flight requests and running quizzes wrap up
+        drop(listener);
+        tracing::info!("draining connections and quizzes within {grace_period:?}");
+        let drain = async { tokio::join!(graceful.shutdown(), state.shutdown()) };
+        if tokio::time::timeout(grace_period, drain).await.is_err() {
+            tracing::warn!("grace period elapsed before everything was drained");
+        }
         anyhow::Ok(())
     })?;
 
-    log::info!("shutting down");
+    tracing::info!("shutting down");
     Ok(())
 }
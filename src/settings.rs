@@ -0,0 +1,695 @@
+//! Typed configuration loaded from an optional TOML file, where environment variables take precedence.
+
+use crate::listener::ListenAddr;
+use api::{ApiUrl, Constraint, RateLimits, Ratelimiter, VerifyingKey};
+use core::{
+    fmt::{self, Display},
+    num::NonZeroU64,
+    str::FromStr,
+    time::Duration,
+};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+/// Environment variable that points to the TOML configuration file.
+pub const CONFIG_VAR: &str = "QUIZZO_CONFIG";
+
+/// Every key that may appear in the configuration file.
+const KEYS: &[&str] = &[
+    "log.level",
+    "log.format",
+    "discord.app_id",
+    "discord.bot_token",
+    "discord.public_key",
+    "discord.api_url",
+    "discord.proxy",
+    "discord.timeout",
+    "discord.ratelimiter",
+    "discord.client_secret",
+    "discord.redirect_uri",
+    "server.bind",
+    "server.max_body_size",
+    "server.read_timeout",
+    "server.replay_window",
+    "server.replay_cache_size",
+    "server.shutdown_grace_period",
+    "server.tls_cert",
+    "server.tls_key",
+    "database.url",
+    "database.ca_cert",
+    "quiz.brand_color",
+    "quiz.default_expiration",
+    "quiz.max_per_user",
+    "quiz.max_per_guild",
+    "webhook.attempts",
+    "webhook.backoff",
+    "rate_limit.user",
+    "rate_limit.guild",
+];
+
+/// Every problem found while loading the configuration.
+#[derive(Debug)]
+pub struct Error(Vec<String>);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid configuration")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Json,
+    Text,
+}
+
+impl FromStr for LogFormat {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            _ => Err("expected `json` or `text`"),
+        }
+    }
+}
+
+/// Directives for [`tracing_subscriber::EnvFilter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLevel(pub String);
+
+impl FromStr for LogLevel {
+    type Err = tracing_subscriber::filter::ParseError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        tracing_subscriber::EnvFilter::try_new(value)?;
+        Ok(Self(value.into()))
+    }
+}
+
+struct PublicKey(VerifyingKey);
+
+impl FromStr for PublicKey {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(value, &mut bytes).map_err(|_| "expected 64 hexadecimal characters")?;
+        VerifyingKey::from_bytes(&bytes).map(Self).map_err(|_| "not a valid Ed25519 public key")
+    }
+}
+
+/// A whole number of seconds.
+struct Seconds(Duration);
+
+impl FromStr for Seconds {
+    type Err = core::num::ParseIntError;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value.parse().map(Duration::from_secs).map(Self)
+    }
+}
+
+/// An RGB color written as `#RRGGBB`, `0xRRGGBB`, or a plain integer.
+struct Color(u32);
+
+impl FromStr for Color {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "expected a color such as `#236EA5`";
+        let color = match value.strip_prefix('#').or_else(|| value.strip_prefix("0x")) {
+            Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| ERROR)?,
+            None => value.parse().map_err(|_| ERROR)?,
+        };
+        if color > 0xFF_FFFF {
+            return Err(ERROR);
+        }
+        Ok(Self(color))
+    }
+}
+
+/// Quiz duration (in seconds) within the range allowed by the database.
+struct Expiration(u16);
+
+impl FromStr for Expiration {
+    type Err = String;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let range = Constraint::EXPIRATION;
+        let error = || format!("expected a number of seconds between {} and {}", range.start(), range.end());
+        let secs = value.parse().map_err(|_| error())?;
+        if !range.contains(&secs) {
+            return Err(error());
+        }
+        Ok(Self(secs))
+    }
+}
+
+/// Maximum number of stored quizzes, where `none` lifts the limit.
+struct QuizLimit(Option<u16>);
+
+impl FromStr for QuizLimit {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "expected a positive number of quizzes or `none`";
+        if value == "none" {
+            return Ok(Self(None));
+        }
+        match value.parse() {
+            Ok(0) | Err(_) => Err(ERROR),
+            Ok(limit) => Ok(Self(Some(limit))),
+        }
+    }
+}
+
+/// Absolute HTTP(S) URL that Discord redirects to after the user authorizes the application.
+struct RedirectUri(String);
+
+impl FromStr for RedirectUri {
+    type Err = &'static str;
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "expected an absolute URL such as `https://quizzo.example.com/oauth/callback`";
+        let uri: hyper::Uri = value.parse().map_err(|_| ERROR)?;
+        match uri.scheme_str() {
+            Some("http" | "https") if uri.host().is_some() => Ok(Self(value.into())),
+            _ => Err(ERROR),
+        }
+    }
+}
+
+/// Looks up an environment variable.
+type Env = Box<dyn Fn(&str) -> Option<String>>;
+
+/// Looks up each setting in the environment first, then in the configuration file.
+struct Source {
+    file: toml::Table,
+    env: Env,
+    problems: Vec<String>,
+}
+
+impl Source {
+    fn new(file: toml::Table, env: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        let mut problems = Vec::new();
+        let mut stack: Vec<_> = file.iter().map(|(key, value)| (key.clone(), value)).collect();
+        while let Some((key, value)) = stack.pop() {
+            match value {
+                toml::Value::Table(table) if !KEYS.contains(&key.as_str()) => {
+                    stack.extend(table.iter().map(|(child, value)| (format!("{key}.{child}"), value)));
+                }
+                _ if KEYS.contains(&key.as_str()) => continue,
+                _ => problems.push(format!("unknown setting `{key}`")),
+            }
+        }
+        problems.sort_unstable();
+        Self { file, env: Box::new(env), problems }
+    }
+
+    /// Reads the file at `QUIZZO_CONFIG` (if set) and the process environment.
+    fn open() -> Result<Self, Error> {
+        let file = match std::env::var_os(CONFIG_VAR) {
+            Some(path) => {
+                let path = std::path::PathBuf::from(path);
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|err| Error(vec![format!("cannot read `{}`: {err}", path.display())]))?;
+                text.parse().map_err(|err| Error(vec![format!("cannot parse `{}`: {err}", path.display())]))?
+            }
+            None => toml::Table::new(),
+        };
+        Ok(Self::new(file, |var| std::env::var(var).ok()))
+    }
+
+    fn raw(&mut self, key: &str, var: &str) -> Option<String> {
+        if let Some(value) = (self.env)(var) {
+            return Some(value);
+        }
+
+        let (section, name) = key.split_once('.')?;
+        let value = self.file.get(section)?.get(name)?;
+        match value {
+            toml::Value::String(value) => Some(value.clone()),
+            toml::Value::Integer(value) => Some(value.to_string()),
+            toml::Value::Float(value) => Some(value.to_string()),
+            toml::Value::Boolean(value) => Some(value.to_string()),
+            _ => {
+                self.problems.push(format!("`{key}` must be a string or a number"));
+                None
+            }
+        }
+    }
+
+    fn parse<T>(&mut self, key: &str, var: &str, value: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.problems.push(format!("`{key}` (or `{var}`) is invalid: {err}"));
+                None
+            }
+        }
+    }
+
+    fn optional<T>(&mut self, key: &str, var: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.raw(key, var)?;
+        self.parse(key, var, &value)
+    }
+
+    fn required<T>(&mut self, key: &str, var: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(value) = self.raw(key, var) else {
+            self.problems.push(format!("`{key}` (or `{var}`) is required"));
+            return None;
+        };
+        self.parse(key, var, &value)
+    }
+
+    fn finish<T>(self, value: Option<T>) -> Result<T, Error> {
+        match value {
+            Some(value) if self.problems.is_empty() => Ok(value),
+            _ => Err(Error(self.problems)),
+        }
+    }
+}
+
+pub struct Log {
+    pub level: LogLevel,
+    pub format: LogFormat,
+}
+
+impl Log {
+    fn load(source: &mut Source) -> Option<Self> {
+        let level = source.optional("log.level", "RUST_LOG").unwrap_or_else(|| LogLevel("error".into()));
+        let format = source.optional("log.format", "LOG_FORMAT").unwrap_or(LogFormat::Json);
+        Some(Self { level, format })
+    }
+}
+
+pub struct Discord {
+    pub app_id: NonZeroU64,
+    pub bot_token: String,
+    pub api_url: Option<ApiUrl>,
+    pub proxy: Option<String>,
+    pub timeout: Option<Duration>,
+    pub ratelimiter: Option<Ratelimiter>,
+    /// Enables browser login via OAuth2.
+    pub oauth: Option<api::OAuthConfig>,
+}
+
+impl Discord {
+    fn load(source: &mut Source) -> Option<Self> {
+        let app_id = source.required("discord.app_id", "APP_ID");
+        let bot_token = source.required("discord.bot_token", "BOT_TOKEN");
+        let api_url = source.optional("discord.api_url", "DISCORD_API_URL");
+        let proxy = source.optional("discord.proxy", "DISCORD_PROXY");
+        let timeout = source.optional("discord.timeout", "DISCORD_TIMEOUT").map(|Seconds(secs)| secs);
+        let ratelimiter = source.optional("discord.ratelimiter", "DISCORD_RATELIMITER");
+        let client_secret = source.optional("discord.client_secret", "DISCORD_CLIENT_SECRET");
+        let redirect_uri = source.optional("discord.redirect_uri", "OAUTH_REDIRECT_URI");
+        let oauth = match (client_secret, redirect_uri) {
+            (Some(client_secret), Some(RedirectUri(redirect_uri))) => {
+                Some(api::OAuthConfig { client_secret, redirect_uri })
+            }
+            (None, None) => None,
+            _ => {
+                source.problems.push("`discord.client_secret` and `discord.redirect_uri` must be set together".into());
+                return None;
+            }
+        };
+        Some(Self { app_id: app_id?, bot_token: bot_token?, api_url, proxy, timeout, ratelimiter, oauth })
+    }
+
+    /// Configures the Discord HTTP client.
+    pub fn builder(&self) -> api::AppBuilder {
+        let mut builder = api::AppBuilder::new(self.app_id, self.bot_token.clone());
+        if let Some(url) = self.api_url.clone() {
+            builder = builder.api_url(url);
+        }
+        if let Some(host) = self.proxy.clone() {
+            builder = builder.proxy(host);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(ratelimiter) = self.ratelimiter {
+            builder = builder.ratelimiter(ratelimiter);
+        }
+        if let Some(config) = self.oauth.clone() {
+            builder = builder.oauth(config);
+        }
+        builder
+    }
+}
+
+pub struct Server {
+    pub bind: ListenAddr,
+    pub public_key: VerifyingKey,
+    pub max_body_size: Option<usize>,
+    pub read_timeout: Option<Duration>,
+    pub replay_window: Option<Duration>,
+    pub replay_cache_size: Option<usize>,
+    pub shutdown_grace_period: Duration,
+    pub tls: Option<TlsFiles>,
+}
+
+/// PEM-encoded certificate chain and private key for terminating TLS.
+pub struct TlsFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+impl Server {
+    fn load(source: &mut Source) -> Option<Self> {
+        // Platforms such as Railway only provide `PORT`, so it overrides the port of the bind address.
+        let problems = source.problems.len();
+        let bind: Option<ListenAddr> = source.optional("server.bind", "BIND_ADDRESS");
+        let port = (source.env)("PORT").and_then(|port| source.parse::<u16>("server.bind", "PORT", &port));
+        let bind = match (bind, port) {
+            (Some(bind), None) => Some(bind),
+            (Some(ListenAddr::Tcp(bind)), Some(port)) => Some(ListenAddr::Tcp(SocketAddr::new(bind.ip(), port))),
+            (Some(ListenAddr::Unix(_)), Some(_)) => {
+                source.problems.push("`PORT` cannot be combined with a Unix socket in `server.bind`".into());
+                None
+            }
+            (None, Some(port)) => Some(ListenAddr::Tcp(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)))),
+            (None, None) => {
+                if source.problems.len() == problems {
+                    source.problems.push("`server.bind` (or `BIND_ADDRESS` or `PORT`) is required".into());
+                }
+                None
+            }
+        };
+
+        let public_key = source.required("discord.public_key", "PUB_KEY").map(|PublicKey(key)| key);
+        let max_body_size = source.optional("server.max_body_size", "MAX_BODY_SIZE");
+        let read_timeout = source.optional("server.read_timeout", "READ_TIMEOUT").map(|Seconds(secs)| secs);
+        let replay_window = source.optional("server.replay_window", "REPLAY_WINDOW").map(|Seconds(secs)| secs);
+        let replay_cache_size = source.optional("server.replay_cache_size", "REPLAY_CACHE_SIZE");
+        let shutdown_grace_period = source
+            .optional("server.shutdown_grace_period", "SHUTDOWN_GRACE_PERIOD")
+            .map_or(Duration::from_secs(30), |Seconds(secs)| secs);
+        let cert = source.optional("server.tls_cert", "TLS_CERT");
+        let key = source.optional("server.tls_key", "TLS_KEY");
+        let tls = match (cert, key) {
+            (Some(cert), Some(key)) => Some(TlsFiles { cert, key }),
+            (None, None) => None,
+            _ => {
+                source.problems.push("`server.tls_cert` and `server.tls_key` must be set together".into());
+                return None;
+            }
+        };
+        Some(Self {
+            bind: bind?,
+            public_key: public_key?,
+            max_body_size,
+            read_timeout,
+            replay_window,
+            replay_cache_size,
+            shutdown_grace_period,
+            tls,
+        })
+    }
+}
+
+pub struct Quiz {
+    pub brand_color: Option<u32>,
+    pub default_expiration: Option<u16>,
+    pub quota: api::Quota,
+}
+
+impl Quiz {
+    fn load(source: &mut Source) -> Option<Self> {
+        let brand_color = source.optional("quiz.brand_color", "BRAND_COLOR").map(|Color(color)| color);
+        let default_expiration =
+            source.optional("quiz.default_expiration", "DEFAULT_EXPIRATION").map(|Expiration(secs)| secs);
+        let mut quota = api::Quota::default();
+        if let Some(QuizLimit(limit)) = source.optional("quiz.max_per_user", "MAX_QUIZZES_PER_USER") {
+            quota.per_user = limit;
+        }
+        if let Some(QuizLimit(limit)) = source.optional("quiz.max_per_guild", "MAX_QUIZZES_PER_GUILD") {
+            quota.per_guild = limit;
+        }
+        Some(Self { brand_color, default_expiration, quota })
+    }
+}
+
+pub struct Webhook {
+    pub attempts: Option<u16>,
+    pub backoff: Option<Duration>,
+}
+
+impl Webhook {
+    fn load(source: &mut Source) -> Option<Self> {
+        let attempts = source.optional("webhook.attempts", "WEBHOOK_ATTEMPTS");
+        let backoff = source.optional("webhook.backoff", "WEBHOOK_BACKOFF").map(|Seconds(secs)| secs);
+        Some(Self { attempts, backoff })
+    }
+}
+
+pub struct RateLimit {
+    pub user: Option<RateLimits>,
+    pub guild: Option<RateLimits>,
+}
+
+impl RateLimit {
+    fn load(source: &mut Source) -> Option<Self> {
+        let user = source.optional("rate_limit.user", "USER_RATE_LIMITS");
+        let guild = source.optional("rate_limit.guild", "GUILD_RATE_LIMITS");
+        Some(Self { user, guild })
+    }
+}
+
+pub struct Database {
+    pub config: api::Config,
+    /// Connects over TLS as strictly as the `sslmode` of the URL demands.
+    pub tls: api::tls::MakeRustlsConnect,
+}
+
+impl Database {
+    fn load(source: &mut Source) -> Option<Self> {
+        let url: Option<api::tls::PgUrl> = source.required("database.url", "PG_URL");
+        let ca_cert = source.optional("database.ca_cert", "PG_CA_CERT");
+        let mut url = url?;
+        if ca_cert.is_some() {
+            url.root_cert = ca_cert;
+        }
+        match url.connector() {
+            Ok(tls) => Some(Self { config: url.config, tls }),
+            Err(err) => {
+                source.problems.push(format!("`database.url` (or `PG_URL`) cannot be used with TLS: {err}"));
+                None
+            }
+        }
+    }
+}
+
+pub struct Settings {
+    pub log: Log,
+    pub discord: Discord,
+    pub server: Server,
+    pub database: Database,
+    pub quiz: Quiz,
+    pub webhook: Webhook,
+    pub rate_limit: RateLimit,
+}
+
+impl Settings {
+    fn from_source(mut source: Source) -> Result<Self, Error> {
+        let log = Log::load(&mut source);
+        let discord = Discord::load(&mut source);
+        let server = Server::load(&mut source);
+        let database = Database::load(&mut source);
+        let quiz = Quiz::load(&mut source);
+        let webhook = Webhook::load(&mut source);
+        let rate_limit = RateLimit::load(&mut source);
+        let settings = (|| {
+            Some(Self {
+                log: log?,
+                discord: discord?,
+                server: server?,
+                database: database?,
+                quiz: quiz?,
+                webhook: webhook?,
+                rate_limit: rate_limit?,
+            })
+        })();
+        source.finish(settings)
+    }
+
+    /// Loads every setting needed to serve interactions.
+    pub fn load() -> Result<Self, Error> {
+        Self::from_source(Source::open()?)
+    }
+
+    /// Loads only the settings needed to register commands.
+    pub fn load_discord() -> Result<(Log, Discord), Error> {
+        let mut source = Source::open()?;
+        let log = Log::load(&mut source);
+        let discord = Discord::load(&mut source);
+        let settings = log.zip(discord);
+        source.finish(settings)
+    }
+
+    /// Loads only the settings needed to manage API keys and webhooks.
+    pub fn load_database() -> Result<(Log, Database), Error> {
+        let mut source = Source::open()?;
+        let log = Log::load(&mut source);
+        let database = Database::load(&mut source);
+        let settings = log.zip(database);
+        source.finish(settings)
+    }
+
+    /// Configures the application from the Discord, server, quiz, webhook, and rate limit settings.
+    pub fn builder(&self) -> api::AppBuilder {
+        let Server { max_body_size, read_timeout, replay_window, replay_cache_size, .. } = self.server;
+        let mut builder = self.discord.builder();
+        if let Some(window) = replay_window {
+            builder = builder.replay_window(window);
+        }
+        if let Some(capacity) = replay_cache_size {
+            builder = builder.replay_capacity(capacity);
+        }
+        if let Some(bytes) = max_body_size {
+            builder = builder.max_body_size(bytes);
+        }
+        if let Some(timeout) = read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(color) = self.quiz.brand_color {
+            builder = builder.brand_color(color);
+        }
+        if let Some(expiration) = self.quiz.default_expiration {
+            builder = builder.default_expiration(expiration);
+        }
+        if let Some(attempts) = self.webhook.attempts {
+            builder = builder.webhook_attempts(attempts);
+        }
+        if let Some(backoff) = self.webhook.backoff {
+            builder = builder.webhook_backoff(backoff);
+        }
+        if let Some(limits) = self.rate_limit.user.clone() {
+            builder = builder.user_rate_limits(limits);
+        }
+        if let Some(limits) = self.rate_limit.guild.clone() {
+            builder = builder.guild_rate_limits(limits);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Duration, LogFormat, Settings, Source};
+    use std::collections::HashMap;
+
+    const PUB_KEY: &str = "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29";
+
+    fn load(file: &str, env: &[(&str, &str)]) -> Result<Settings, String> {
+        let env: HashMap<_, _> = env.iter().map(|&(var, value)| (var.to_owned(), value.to_owned())).collect();
+        let source = Source::new(file.parse().unwrap(), move |var| env.get(var).cloned());
+        Settings::from_source(source).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn file_with_env_overrides() {
+        let file = format!(
+            r##"
+            [discord]
+            app_id = 42
+            bot_token = "file"
+            public_key = "{PUB_KEY}"
+            client_secret = "secret"
+            redirect_uri = "https://quizzo.example.com/oauth/callback"
+
+            [server]
+            bind = "127.0.0.1:8080"
+            shutdown_grace_period = 5
+
+            [database]
+            url = "postgres://runner@localhost/quizzo"
+
+            [quiz]
+            brand_color = "#FF0000"
+            default_expiration = 60
+            max_per_user = "none"
+
+            [webhook]
+            backoff = 2
+
+            [rate_limit]
+            user = "*=5/10, answer=off"
+            "##
+        );
+        let env = [("BOT_TOKEN", "env"), ("PORT", "3000"), ("LOG_FORMAT", "text"), ("MAX_QUIZZES_PER_GUILD", "100")];
+        let settings = load(&file, &env).unwrap();
+        assert_eq!(settings.discord.app_id.get(), 42);
+        assert_eq!(settings.discord.bot_token, "env");
+        assert!(settings.discord.oauth.is_some());
+        assert_eq!(settings.server.bind.to_string(), "127.0.0.1:3000");
+        assert_eq!(settings.server.shutdown_grace_period, Duration::from_secs(5));
+        assert_eq!(settings.quiz.brand_color, Some(0xFF0000));
+        assert_eq!(settings.quiz.default_expiration, Some(60));
+        assert_eq!(settings.quiz.quota, api::Quota { per_user: None, per_guild: Some(100) });
+        assert_eq!(settings.webhook.attempts, None);
+        assert_eq!(settings.webhook.backoff, Some(Duration::from_secs(2)));
+        assert_eq!(settings.rate_limit.user, Some("answer=off,*=5/10".parse().unwrap()));
+        assert_eq!(settings.rate_limit.guild, None);
+        assert_eq!(settings.log.format, LogFormat::Text);
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let file = r#"
+            [discord]
+            app_id = 0
+            public_key = "not hex"
+
+            client_secret = "secret"
+
+            [server]
+            prot = 3000
+            tls_cert = "cert.pem"
+
+            [quiz]
+            brand_color = "red"
+            default_expiration = 5
+            max_per_user = 0
+
+            [rate_limit]
+            user = "create=fast"
+            guild = "join=1/10"
+        "#;
+        let Err(err) = load(file, &[]) else {
+            panic!("invalid settings were accepted");
+        };
+        for problem in [
+            "`discord.app_id` (or `APP_ID`) is invalid",
+            "`discord.bot_token` (or `BOT_TOKEN`) is required",
+            "`discord.public_key` (or `PUB_KEY`) is invalid: expected 64 hexadecimal characters",
+            "`discord.client_secret` and `discord.redirect_uri` must be set together",
+            "`server.bind` (or `BIND_ADDRESS` or `PORT`) is required",
+            "unknown setting `server.prot`",
+            "`server.tls_cert` and `server.tls_key` must be set together",
+            "`database.url` (or `PG_URL`) is required",
+            "`quiz.brand_color` (or `BRAND_COLOR`) is invalid",
+            "`quiz.default_expiration` (or `DEFAULT_EXPIRATION`) is invalid: expected a number of seconds between 10 and 600",
+            "`quiz.max_per_user` (or `MAX_QUIZZES_PER_USER`) is invalid: expected a positive number of quizzes or `none`",
+            "`rate_limit.user` (or `USER_RATE_LIMITS`) is invalid: expected `BURST/SECONDS` or `off` instead of `fast`",
+            "`rate_limit.guild` (or `GUILD_RATE_LIMITS`) is invalid: unknown command `join`",
+        ] {
+            assert!(err.contains(problem), "missing `{problem}` in:\n{err}");
+        }
+    }
+}
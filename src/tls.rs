@@ -0,0 +1,127 @@
+//! TLS termination with a certificate that can be reloaded without a restart.
+
+use crate::settings::TlsFiles;
+use anyhow::Context as _;
+use std::{
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        server::{ClientHello, ResolvesServerCert},
+        sign::CertifiedKey,
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+/// Hands out whichever certificate was loaded last.
+#[derive(Debug)]
+struct Resolver(RwLock<Arc<CertifiedKey>>);
+
+impl Resolver {
+    fn current(&self) -> Arc<CertifiedKey> {
+        let key = self.0.read().unwrap_or_else(|poison| poison.into_inner());
+        Arc::clone(&key)
+    }
+}
+
+impl ResolvesServerCert for Resolver {
+    fn resolve(&self, _: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+pub struct Tls {
+    files: TlsFiles,
+    resolver: Arc<Resolver>,
+    acceptor: TlsAcceptor,
+}
+
+impl Tls {
+    pub fn load(files: TlsFiles) -> anyhow::Result<Self> {
+        let key = certified_key(&files)?;
+        let resolver = Arc::new(Resolver(RwLock::new(Arc::new(key))));
+        let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver.clone());
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(Self { files, resolver, acceptor: TlsAcceptor::from(Arc::new(config)) })
+    }
+
+    /// Re-reads the certificate and key from disk. The previous pair stays in use if either cannot be loaded.
+    pub fn reload(&self) -> anyhow::Result<()> {
+        let key = Arc::new(certified_key(&self.files)?);
+        *self.resolver.0.write().unwrap_or_else(|poison| poison.into_inner()) = key;
+        Ok(())
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.clone()
+    }
+}
+
+fn open(path: &Path) -> anyhow::Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("cannot open `{}`", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+fn certified_key(TlsFiles { cert, key }: &TlsFiles) -> anyhow::Result<CertifiedKey> {
+    let chain = rustls_pemfile::certs(&mut open(cert)?)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("cannot parse certificates in `{}`", cert.display()))?;
+    anyhow::ensure!(!chain.is_empty(), "no certificates found in `{}`", cert.display());
+
+    let der = rustls_pemfile::private_key(&mut open(key)?)
+        .with_context(|| format!("cannot parse private key in `{}`", key.display()))?
+        .with_context(|| format!("no private key found in `{}`", key.display()))?;
+    let signer = ring::sign::any_supported_type(&der)
+        .with_context(|| format!("unsupported private key in `{}`", key.display()))?;
+
+    let certified = CertifiedKey::new(chain, signer);
+    certified.keys_match().context("the private key does not match the certificate")?;
+    Ok(certified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tls, TlsFiles};
+    use std::{fs, path::PathBuf};
+
+    fn fixture(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests", "tls", name].iter().collect()
+    }
+
+    #[test]
+    fn reload_swaps_certificates() {
+        let dir = std::env::temp_dir().join(format!("quizzo-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files = TlsFiles { cert: dir.join("cert.pem"), key: dir.join("key.pem") };
+        let install = |name: &str| {
+            fs::copy(fixture(&format!("{name}.crt")), &files.cert).unwrap();
+            fs::copy(fixture(&format!("{name}.key")), &files.key).unwrap();
+        };
+
+        install("first");
+        let tls = Tls::load(TlsFiles { cert: files.cert.clone(), key: files.key.clone() }).unwrap();
+        let first = tls.resolver.current();
+
+        install("second");
+        tls.reload().unwrap();
+        let second = tls.resolver.current();
+        assert_ne!(first.cert, second.cert);
+        let expected = rustls_pemfile::certs(&mut super::open(&fixture("second.crt")).unwrap()).next().unwrap();
+        assert_eq!(second.cert, [expected.unwrap()]);
+
+        // A bad key keeps the previous pair in use
+        fs::write(&files.key, "not a key").unwrap();
+        assert!(tls.reload().is_err());
+        assert_eq!(tls.resolver.current().cert, second.cert);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}